@@ -1,18 +1,18 @@
 #![cfg(feature = "ffi")]
 
 use crate::*;
-use crypto_common::*;
+use crypto_common::{rng::secure_rng, *};
 use ffi_helpers::*;
 use id::sigma_protocols::dlog;
 use pairing::bls12_381::Bls12;
-use rand::{rngs::StdRng, thread_rng, SeedableRng};
+use rand::{rngs::StdRng, SeedableRng};
 use random_oracle::RandomOracle;
 use std::{cmp::Ordering, slice};
 
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn bls_generate_secretkey() -> *mut SecretKey<Bls12> {
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     Box::into_raw(Box::new(SecretKey::generate(&mut csprng)))
 }
 
@@ -170,7 +170,7 @@ pub extern "C" fn bls_prove(
     let sk = from_ptr!(sk_ptr);
 
     let mut ro = RandomOracle::domain(ro_bytes);
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     let prf = sk.prove(&mut csprng, &mut ro);
     Box::into_raw(Box::new(prf))
 }
@@ -196,7 +196,7 @@ pub extern "C" fn bls_check_proof(
 #[cfg(test)]
 mod test {
     use super::*;
-    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 
     #[test]
     fn test_verify_aggregate_ffi() {
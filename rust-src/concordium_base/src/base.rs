@@ -1237,6 +1237,268 @@ impl<'de> SerdeDeserialize<'de> for MintRate {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crypto_common::{from_bytes, from_bytes_exact, to_bytes};
+
+    #[test]
+    fn test_url_text_serial() {
+        // The length is serialized as a big endian u16, followed by the UTF-8 bytes.
+        let url = UrlText::try_from("ab".to_string()).expect("Short URL is valid.");
+        assert_eq!(to_bytes(&url), vec![0, 2, b'a', b'b']);
+        let url2: UrlText = from_bytes(&mut std::io::Cursor::new(to_bytes(&url))).unwrap();
+        assert_eq!(url, url2);
+        assert!(
+            from_bytes::<UrlText, _>(&mut std::io::Cursor::new([0u8, 3, b'a', b'b'])).is_err(),
+            "Declared length exceeds the data."
+        );
+    }
+
+    #[test]
+    fn test_derive_field_order() {
+        #[derive(Serialize, Debug, PartialEq)]
+        struct Declared {
+            a: u8,
+            b: u32,
+            c: u16,
+        }
+        #[derive(Serialize, Debug, PartialEq)]
+        struct Reordered {
+            #[concordium(order = 2)]
+            c: u16,
+            #[concordium(order = 0)]
+            a: u8,
+            #[concordium(order = 1)]
+            b: u32,
+        }
+        #[derive(Serialize, Debug, PartialEq)]
+        struct ReorderedTuple(
+            #[concordium(order = 1)] u32,
+            #[concordium(order = 2)] u16,
+            #[concordium(order = 0)] u8,
+        );
+        let declared = Declared { a: 1, b: 2, c: 3 };
+        let reordered = Reordered { c: 3, a: 1, b: 2 };
+        let tuple = ReorderedTuple(2, 3, 1);
+        let bytes = to_bytes(&declared);
+        assert_eq!(bytes, to_bytes(&reordered));
+        assert_eq!(bytes, to_bytes(&tuple));
+        let reordered2: Reordered = from_bytes(&mut std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(reordered, reordered2);
+        let tuple2: ReorderedTuple = from_bytes(&mut std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(tuple, tuple2);
+    }
+
+    #[test]
+    fn test_derive_phantom_data() {
+        // Deliberately not serializable.
+        #[derive(Debug, PartialEq)]
+        struct Marker;
+        #[derive(Serialize, Debug, PartialEq)]
+        struct Tagged<T> {
+            value:    u32,
+            _phantom: std::marker::PhantomData<T>,
+        }
+        let tagged = Tagged::<Marker> {
+            value:    17,
+            _phantom: Default::default(),
+        };
+        let bytes = to_bytes(&tagged);
+        assert_eq!(bytes, to_bytes(&17u32), "PhantomData takes up no bytes.");
+        let parsed: Tagged<Marker> = from_bytes(&mut std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(parsed, tagged);
+    }
+
+    #[test]
+    fn test_derive_length_bounds() {
+        #[derive(Serialize, Debug, PartialEq)]
+        struct Bounded {
+            #[size_length = 1]
+            #[concordium(min_len = 1, max_len = 3)]
+            values: Vec<u8>,
+            #[set_size_length = 1]
+            #[concordium(max_len = 2)]
+            set:    std::collections::BTreeSet<u8>,
+        }
+        let parse = |values: Vec<u8>, set: &[u8]| {
+            let bounded = Bounded {
+                values,
+                set: set.iter().copied().collect(),
+            };
+            let bytes = to_bytes(&bounded);
+            from_bytes::<Bounded, _>(&mut std::io::Cursor::new(&bytes))
+                .map(|parsed| assert_eq!(parsed, bounded))
+        };
+        assert!(parse(vec![], &[]).is_err(), "Below the minimum.");
+        assert!(parse(vec![1, 2, 3, 4], &[]).is_err(), "Above the maximum.");
+        assert!(
+            parse(vec![1], &[1, 2, 3]).is_err(),
+            "Set above the maximum."
+        );
+        assert!(parse(vec![1], &[]).is_ok(), "Minimum length.");
+        assert!(parse(vec![1, 2, 3], &[1, 2]).is_ok(), "Maximum length.");
+    }
+
+    #[test]
+    fn test_derive_length_overflow() {
+        #[derive(Serialize, Debug, PartialEq)]
+        struct SmallMap {
+            #[map_size_length = 1]
+            map: std::collections::BTreeMap<u16, u8>,
+        }
+        let full = SmallMap {
+            map: (0..255).map(|i| (i, 0)).collect(),
+        };
+        let bytes = to_bytes(&full);
+        assert_eq!(bytes[0], 255, "The length fits in one byte.");
+        let parsed = from_bytes::<SmallMap, _>(&mut std::io::Cursor::new(&bytes));
+        assert_eq!(parsed.ok(), Some(full));
+        let too_big = SmallMap {
+            map: (0..256).map(|i| (i, 0)).collect(),
+        };
+        assert!(
+            std::panic::catch_unwind(|| to_bytes(&too_big)).is_err(),
+            "A length that does not fit should not be truncated."
+        );
+    }
+
+    #[test]
+    fn test_derive_map_from_sorted_vec() {
+        #[derive(Serialize, Debug, PartialEq)]
+        struct AsMap {
+            #[map_size_length = 2]
+            map: std::collections::BTreeMap<u16, u8>,
+        }
+        #[derive(Serialize, Debug, PartialEq)]
+        struct AsVec {
+            #[map_size_length = 2]
+            #[concordium(map_from_sorted_vec)]
+            map: Vec<(u16, u8)>,
+        }
+        #[derive(Serialize, Debug, PartialEq)]
+        struct AsVecUnnamed(
+            #[map_size_length = 2]
+            #[concordium(map_from_sorted_vec)]
+            Vec<(u16, u8)>,
+        );
+        let entries = vec![(1, 10), (5, 50), (300, 30)];
+        let as_vec = AsVec {
+            map: entries.clone(),
+        };
+        let as_map = AsMap {
+            map: entries.iter().copied().collect(),
+        };
+        let bytes = to_bytes(&as_vec);
+        assert_eq!(bytes, to_bytes(&as_map), "Same format as a map.");
+        assert_eq!(bytes, to_bytes(&AsVecUnnamed(entries)));
+        assert_eq!(from_bytes_exact::<AsVec>(&bytes).ok(), Some(as_vec));
+        // Swap the first two entries, and repeat the first key.
+        let mut out_of_order = bytes.clone();
+        out_of_order[2..8].rotate_left(3);
+        assert!(from_bytes_exact::<AsVec>(&out_of_order).is_err());
+        assert!(from_bytes_exact::<AsVecUnnamed>(&out_of_order).is_err());
+        let mut duplicate = bytes;
+        duplicate[5..7].copy_from_slice(&[0, 1]);
+        assert!(from_bytes_exact::<AsVec>(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_derive_enum_tags() {
+        #[derive(Serialize, Debug, PartialEq)]
+        enum Original {
+            #[concordium(tag = 0)]
+            Unit,
+            #[concordium(tag = 1)]
+            Tuple(u32, bool),
+            #[concordium(tag = 7)]
+            Named { a: u8, b: Vec<u16> },
+        }
+        #[derive(Serialize, Debug, PartialEq)]
+        enum Reordered {
+            #[concordium(tag = 7)]
+            Named { a: u8, b: Vec<u16> },
+            #[concordium(tag = 0)]
+            Unit,
+            #[concordium(tag = 1)]
+            Tuple(u32, bool),
+        }
+        #[derive(Serialize, Debug, PartialEq)]
+        enum Implicit {
+            Unit,
+            Tuple(u32, bool),
+        }
+        let pairs = vec![
+            (Original::Unit, Reordered::Unit),
+            (Original::Tuple(17, true), Reordered::Tuple(17, true)),
+            (
+                Original::Named {
+                    a: 3,
+                    b: vec![1, 2],
+                },
+                Reordered::Named {
+                    a: 3,
+                    b: vec![1, 2],
+                },
+            ),
+        ];
+        for (original, reordered) in pairs {
+            let bytes = to_bytes(&original);
+            assert_eq!(bytes, to_bytes(&reordered), "Tags pin the encoding.");
+            let parsed = from_bytes::<Reordered, _>(&mut std::io::Cursor::new(&bytes));
+            assert_eq!(parsed.ok(), Some(reordered));
+            let parsed = from_bytes::<Original, _>(&mut std::io::Cursor::new(&bytes));
+            assert_eq!(parsed.ok(), Some(original));
+        }
+        assert_eq!(to_bytes(&Original::Named { a: 3, b: vec![] })[0], 7);
+        assert_eq!(
+            to_bytes(&Implicit::Tuple(17, true)),
+            to_bytes(&Original::Tuple(17, true))
+        );
+        assert_eq!(to_bytes(&Implicit::Unit), vec![0]);
+        assert!(
+            from_bytes::<Original, _>(&mut std::io::Cursor::new(&[2u8])).is_err(),
+            "Unknown tags are rejected."
+        );
+    }
+
+    #[test]
+    fn test_derive_checksum() {
+        #[derive(Serialize, Debug, PartialEq)]
+        #[concordium(checksum = "crc32")]
+        struct Checked {
+            value: u64,
+            #[string_size_length = 2]
+            name:  String,
+        }
+        #[derive(Serialize, Debug, PartialEq)]
+        #[concordium(checksum = "crc32")]
+        struct CheckedTuple(u32, u8);
+
+        let checked = Checked {
+            value: 17,
+            name:  "checksum".into(),
+        };
+        let bytes = to_bytes(&checked);
+        assert_eq!(bytes.len(), 8 + 2 + 8 + 4, "The checksum is 4 bytes.");
+        let parsed = from_bytes::<Checked, _>(&mut std::io::Cursor::new(&bytes));
+        assert_eq!(parsed.ok(), Some(checked));
+        for i in 0..bytes.len() {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 0x01;
+            assert!(
+                from_bytes::<Checked, _>(&mut std::io::Cursor::new(&corrupted)).is_err(),
+                "A flipped byte at {} should be detected.",
+                i
+            );
+        }
+
+        let tuple = CheckedTuple(3, 4);
+        let mut bytes = to_bytes(&tuple);
+        assert_eq!(bytes.len(), 4 + 1 + 4);
+        let parsed = from_bytes::<CheckedTuple, _>(&mut std::io::Cursor::new(&bytes));
+        assert_eq!(parsed.ok(), Some(tuple));
+        bytes[4] ^= 0x80;
+        assert!(from_bytes::<CheckedTuple, _>(&mut std::io::Cursor::new(&bytes)).is_err());
+    }
+
     #[test]
     fn test_parts_0() {
         assert_eq!(
@@ -0,0 +1,86 @@
+//! Checksums for detecting corruption of serialized data. These are not
+//! cryptographic, and do not protect against deliberate modification.
+
+/// Incremental computation of the CRC-32 checksum, as used by, e.g., zlib and
+/// Ethernet (polynomial `0x04C11DB7`, reflected).
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self { Crc32 { state: !0 } }
+}
+
+impl Crc32 {
+    pub fn new() -> Self { Self::default() }
+
+    /// Add the bytes to the checksum.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.state ^= u32::from(b);
+            for _ in 0..8 {
+                let mask = (self.state & 1).wrapping_neg();
+                self.state = (self.state >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+    }
+
+    /// The checksum of all the bytes added so far.
+    pub fn checksum(&self) -> u32 { !self.state }
+}
+
+/// Compute the CRC-32 checksum of the bytes.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.checksum()
+}
+
+/// A reader that computes the CRC-32 checksum of the bytes read through it.
+pub struct Crc32Reader<R> {
+    inner: R,
+    crc:   Crc32,
+}
+
+impl<R: std::io::Read> Crc32Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Crc32Reader {
+            inner,
+            crc: Crc32::new(),
+        }
+    }
+
+    /// The checksum of the bytes read so far.
+    pub fn checksum(&self) -> u32 { self.crc.checksum() }
+}
+
+impl<R: std::io::Read> std::io::Read for Crc32Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+        let mut reader = Crc32Reader::new(&b"123456789"[..]);
+        let mut buf = [0u8; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.checksum(), crc32(b"1234"));
+        reader.read_to_end(&mut Vec::new()).unwrap();
+        assert_eq!(reader.checksum(), 0xCBF4_3926);
+    }
+}
@@ -0,0 +1,26 @@
+//! Constant-time comparison of byte strings. Comparisons of secret bytes, or
+//! of bytes derived from secrets, should go through this module instead of
+//! `==`, which may return as soon as the first difference is found.
+
+use subtle::ConstantTimeEq;
+
+/// Check whether the two byte strings are equal. The running time does not
+/// depend on the contents of the inputs, only on their lengths. Inputs of
+/// different lengths are never equal, and the lengths are not considered
+/// secret.
+pub fn ct_eq_bytes(a: &[u8], b: &[u8]) -> bool { a.ct_eq(b).into() }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ct_eq_bytes() {
+        assert!(ct_eq_bytes(&[], &[]), "Empty inputs are equal.");
+        assert!(ct_eq_bytes(&[1, 2, 3], &[1, 2, 3]), "Equal inputs.");
+        assert!(!ct_eq_bytes(&[1, 2, 3], &[1, 2, 4]), "Last byte differs.");
+        assert!(!ct_eq_bytes(&[0, 2, 3], &[1, 2, 3]), "First byte differs.");
+        assert!(!ct_eq_bytes(&[1, 2, 3], &[1, 2]), "Different lengths.");
+        assert!(!ct_eq_bytes(&[], &[0]), "Different lengths.");
+    }
+}
@@ -0,0 +1,57 @@
+//! Unambiguous encoding of several byte strings as a single hash input.
+
+use sha2::{Digest, Sha256};
+
+/// A builder for hash inputs consisting of several segments. Each segment is
+/// prefixed with its length as a big-endian `u64`, so that the segments can
+/// be recovered from the encoding. In particular appending `a` and then `b`
+/// gives a different input than appending the concatenation of `a` and `b`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashInput {
+    bytes: Vec<u8>,
+}
+
+impl HashInput {
+    pub fn new() -> Self { Self::default() }
+
+    /// Append a segment to the input.
+    pub fn append(&mut self, segment: impl AsRef<[u8]>) -> &mut Self {
+        let segment = segment.as_ref();
+        self.bytes
+            .extend_from_slice(&(segment.len() as u64).to_be_bytes());
+        self.bytes.extend_from_slice(segment);
+        self
+    }
+
+    /// Append a segment to the input, consuming and returning the builder.
+    pub fn with(mut self, segment: impl AsRef<[u8]>) -> Self {
+        self.append(segment);
+        self
+    }
+
+    /// The encoding of the segments appended so far.
+    pub fn as_bytes(&self) -> &[u8] { &self.bytes }
+
+    /// Hash the encoded segments with SHA-256.
+    pub fn finalize_sha256(&self) -> [u8; 32] { Sha256::digest(&self.bytes).into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_are_separated() {
+        let separate = HashInput::new().with(b"ab").with(b"cd");
+        let joined = HashInput::new().with(b"abcd");
+        let shifted = HashInput::new().with(b"a").with(b"bcd");
+        let empty = HashInput::new().with(b"").with(b"abcd");
+        assert_ne!(separate.finalize_sha256(), joined.finalize_sha256());
+        assert_ne!(separate.finalize_sha256(), shifted.finalize_sha256());
+        assert_ne!(joined.finalize_sha256(), empty.finalize_sha256());
+        assert_eq!(
+            separate.as_bytes(),
+            &[0, 0, 0, 0, 0, 0, 0, 2, b'a', b'b', 0, 0, 0, 0, 0, 0, 0, 2, b'c', b'd'][..]
+        );
+    }
+}
@@ -9,3 +9,51 @@ pub fn serialize_deserialize<A: Serialize>(x: &A) -> ParseResult<A> {
     x.serial(&mut buf);
     A::deserial(&mut Cursor::new(buf))
 }
+
+/// Check that `iterations` values produced by `generate` survive a
+/// serialization round trip with [from_bytes_exact], and that deserialization
+/// fails without panicking on every proper prefix of their serialization and
+/// on random bytes of up to twice its length. Used for testing, it panics if
+/// any of the checks fails.
+pub fn assert_serialization_roundtrip<A, R, F>(csprng: &mut R, iterations: usize, mut generate: F)
+where
+    A: Serialize + PartialEq + std::fmt::Debug,
+    R: rand::Rng,
+    F: FnMut(&mut R) -> A, {
+    for _ in 0..iterations {
+        let x = generate(csprng);
+        let bytes = to_bytes(&x);
+        match from_bytes_exact::<A>(&bytes) {
+            Ok(y) => assert_eq!(x, y, "Value changed in the round trip."),
+            Err(e) => panic!("Could not deserialize {:?}: {}", x, e),
+        }
+        for len in 0..bytes.len() {
+            assert!(
+                from_bytes_exact::<A>(&bytes[..len]).is_err(),
+                "Prefix of length {} of the serialization of {:?} was accepted.",
+                len,
+                x
+            );
+        }
+        let mut garbage = vec![0u8; csprng.gen_range(0, 2 * bytes.len() + 1)];
+        csprng.fill_bytes(&mut garbage);
+        // Only checks that this does not panic. Random bytes may be valid.
+        let _ = from_bytes_exact::<A>(&garbage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_assert_serialization_roundtrip() {
+        let mut csprng = rand::thread_rng();
+        assert_serialization_roundtrip(&mut csprng, 20, |rng| {
+            let len = rng.gen_range(0, 10);
+            (0..len).map(|_| rng.gen::<u64>()).collect::<Vec<u64>>()
+        });
+        assert_serialization_roundtrip(&mut csprng, 20, |rng| (rng.gen::<u8>(), rng.gen::<u32>()));
+    }
+}
@@ -51,6 +51,14 @@ impl Serial for G1 {
     }
 }
 
+impl SerialFixed<48> for G1 {
+    fn to_array(&self) -> [u8; 48] {
+        let mut out = [0u8; 48];
+        out.copy_from_slice(self.into_affine().into_compressed().as_ref());
+        out
+    }
+}
+
 impl Deserial for G1Affine {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<G1Affine> {
         let mut g = G1Compressed::empty();
@@ -72,6 +80,14 @@ impl Serial for G1Affine {
     }
 }
 
+impl SerialFixed<48> for G1Affine {
+    fn to_array(&self) -> [u8; 48] {
+        let mut out = [0u8; 48];
+        out.copy_from_slice(self.into_compressed().as_ref());
+        out
+    }
+}
+
 impl Deserial for G2 {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<G2> {
         let mut g = G2Compressed::empty();
@@ -93,6 +109,14 @@ impl Serial for G2 {
     }
 }
 
+impl SerialFixed<96> for G2 {
+    fn to_array(&self) -> [u8; 96] {
+        let mut out = [0u8; 96];
+        out.copy_from_slice(self.into_affine().into_compressed().as_ref());
+        out
+    }
+}
+
 impl Deserial for G2Affine {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<G2Affine> {
         let mut g = G2Compressed::empty();
@@ -114,6 +138,14 @@ impl Serial for G2Affine {
     }
 }
 
+impl SerialFixed<96> for G2Affine {
+    fn to_array(&self) -> [u8; 96] {
+        let mut out = [0u8; 96];
+        out.copy_from_slice(self.into_compressed().as_ref());
+        out
+    }
+}
+
 /// This implementation is ad-hoc, using the fact that Fq12 is defined
 /// via that specific tower of extensions (of degrees) 2 -> 3 -> 2,
 /// and the specific representation of those fields.
@@ -163,9 +195,11 @@ impl Serial for PublicKey {
 
 impl Deserial for SecretKey {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
-        let mut buf = [0u8; SECRET_KEY_LENGTH];
-        source.read_exact(&mut buf)?;
-        Ok(SecretKey::from_bytes(&buf)?)
+        // The buffer is cleared when it goes out of scope, so that no copy of
+        // the key is left behind.
+        let mut buf = zeroize::Zeroizing::new([0u8; SECRET_KEY_LENGTH]);
+        source.read_exact(&mut buf[..])?;
+        Ok(SecretKey::from_bytes(&buf[..])?)
     }
 }
 
@@ -178,9 +212,9 @@ impl Serial for SecretKey {
 
 impl Deserial for Keypair {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
-        let mut buf = [0u8; KEYPAIR_LENGTH];
-        source.read_exact(&mut buf)?;
-        Ok(Keypair::from_bytes(&buf)?)
+        let mut buf = zeroize::Zeroizing::new([0u8; KEYPAIR_LENGTH]);
+        source.read_exact(&mut buf[..])?;
+        Ok(Keypair::from_bytes(&buf[..])?)
     }
 }
 
@@ -1,7 +1,11 @@
 //! Common types and operations used throughout the Concordium chain
 //! development.
+pub mod checksum;
+pub mod ct;
+pub mod hash_input;
 mod helpers;
 mod impls;
+pub mod rng;
 mod serde_impls;
 mod serialize;
 pub mod types;
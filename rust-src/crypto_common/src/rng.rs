@@ -0,0 +1,32 @@
+//! Acquisition of random number generators. Library code should obtain its
+//! randomness through these functions, so that there is a single place to
+//! audit, and so that tests can be made deterministic by using
+//! [seeded_rng].
+
+use rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
+
+/// A cryptographically secure random number generator seeded from the
+/// operating system. This is the generator to use for keys, randomness of
+/// encryptions and commitments, and proofs.
+pub fn secure_rng() -> impl CryptoRng + RngCore { rand::thread_rng() }
+
+/// A cryptographically secure random number generator that is fully
+/// determined by the seed. Two generators with the same seed produce the same
+/// sequence of values. This must only be used with a secret, uniformly random
+/// seed, or in tests.
+pub fn seeded_rng(seed: &[u8; 32]) -> impl CryptoRng + RngCore { StdRng::from_seed(*seed) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_rng_deterministic() {
+        let mut r1 = seeded_rng(&[1u8; 32]);
+        let mut r2 = seeded_rng(&[1u8; 32]);
+        let mut r3 = seeded_rng(&[2u8; 32]);
+        let xs = (0..10).map(|_| r1.next_u64()).collect::<Vec<_>>();
+        assert_eq!(xs, (0..10).map(|_| r2.next_u64()).collect::<Vec<_>>());
+        assert_ne!(xs, (0..10).map(|_| r3.next_u64()).collect::<Vec<_>>());
+    }
+}
@@ -5,9 +5,7 @@ use concordium_contracts_common::ExchangeRate;
 use core::cmp;
 use sha2::Digest;
 use std::{
-    collections::btree_map::BTreeMap,
-    convert::{TryFrom, TryInto},
-    marker::PhantomData,
+    collections::btree_map::BTreeMap, convert::TryFrom, marker::PhantomData, mem::MaybeUninit,
 };
 
 static MAX_PREALLOCATED_CAPACITY: usize = 4096;
@@ -26,6 +24,84 @@ pub fn safe_with_capacity<T>(capacity: usize) -> Vec<T> {
     Vec::with_capacity(cmp::min(capacity, MAX_PREALLOCATED_CAPACITY))
 }
 
+/// A limit on the total memory, in bytes, that the collections in a value may
+/// take up when it is deserialized with [deserial_with_budget]. This bounds
+/// the allocation for nested values such as `Vec<Vec<Vec<u8>>>`, where each
+/// collection may be small, but there may be many of them.
+///
+/// The budget is charged for the declared length of each vector, map, set,
+/// string, and byte array when it is read, multiplied by the size of an
+/// element in memory (at least 1 byte). Values that are not collections are
+/// not charged. The memory that boxed or otherwise indirect elements point to
+/// is charged when they are read, if they are collections themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeserialBudget {
+    remaining: usize,
+}
+
+/// The budget of [DeserialBudget::default], 256MiB.
+pub const DEFAULT_DESERIAL_BUDGET: usize = 256 << 20;
+
+impl Default for DeserialBudget {
+    fn default() -> Self { DeserialBudget::new(DEFAULT_DESERIAL_BUDGET) }
+}
+
+impl DeserialBudget {
+    /// A budget of the given number of bytes.
+    pub fn new(bytes: usize) -> Self { DeserialBudget { remaining: bytes } }
+
+    /// The number of bytes that are left of the budget.
+    pub fn remaining(&self) -> usize { self.remaining }
+}
+
+thread_local! {
+    /// The budget of the innermost [deserial_with_budget] call on this
+    /// thread, if any. The collection helpers charge it, so that it applies to
+    /// all implementations of [Deserial] without changing the trait.
+    static DESERIAL_BUDGET: std::cell::Cell<Option<usize>> = const { std::cell::Cell::new(None) };
+}
+
+/// Charge the budget, if there is one, for a collection of `len` elements of
+/// type `T`. Hand-written [Deserial] instances that read collections element by
+/// element should call this before allocating.
+pub fn charge_deserial_budget<T>(len: usize) -> ParseResult<()> {
+    DESERIAL_BUDGET.with(|budget| {
+        if let Some(remaining) = budget.get() {
+            let cost = len.saturating_mul(cmp::max(std::mem::size_of::<T>(), 1));
+            if cost > remaining {
+                bail!(
+                    "Deserialization budget exhausted: {} bytes needed, {} remaining.",
+                    cost,
+                    remaining
+                )
+            }
+            budget.set(Some(remaining - cost));
+        }
+        Ok(())
+    })
+}
+
+/// Deserialize a value, failing if its collections take up more memory than
+/// the budget allows, cf. [DeserialBudget]. The budget is decreased by the
+/// amount used, also if deserialization fails, so it can be shared by several
+/// values. A nested call uses only its own budget.
+pub fn deserial_with_budget<A: Deserial, R: ReadBytesExt>(
+    source: &mut R,
+    budget: &mut DeserialBudget,
+) -> ParseResult<A> {
+    /// Restores the enclosing budget, also if deserialization panics.
+    struct Restore(Option<usize>);
+    impl Drop for Restore {
+        fn drop(&mut self) { DESERIAL_BUDGET.with(|b| b.set(self.0)) }
+    }
+    let _restore = Restore(DESERIAL_BUDGET.with(|b| b.replace(Some(budget.remaining))));
+    let result = A::deserial(source);
+    budget.remaining = DESERIAL_BUDGET
+        .with(|b| b.get())
+        .expect("The budget is set for the duration of the call.");
+    result
+}
+
 /// Trait for types which can be recovered from byte sources.
 pub trait Deserial: Sized {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self>;
@@ -66,6 +142,24 @@ impl Deserial for bool {
     }
 }
 
+/// A `char` is read as a u32 in big endian, which must be a valid Unicode
+/// scalar value.
+impl Deserial for char {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let x: u32 = source.get()?;
+        std::char::from_u32(x).with_context(|| format!("Invalid code point {:#x}.", x))
+    }
+}
+
+/// Read a string where the first 4 bytes are taken as the length of its UTF-8
+/// encoding in big endian.
+impl Deserial for String {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let len: u32 = source.get()?;
+        deserial_string(source, usize::try_from(len)?)
+    }
+}
+
 impl Deserial for u8 {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<u8> { Ok(source.read_u8()?) }
 }
@@ -193,13 +287,20 @@ impl<T: Deserial, S: Deserial, U: Deserial> Deserial for (T, S, U) {
     }
 }
 
-/// Read a string of given size.
-/// NB: Be aware that this allocates a buffer of the given length, and so this
-/// must only be used when the size is bounded, otherwise it will lead to a
-/// memory allocation failure, and panic.
+/// Read a string of given size, and check that it is valid UTF-8.
+/// The buffer grows with the data that is actually read, so a declared length
+/// exceeding the available data leads to an error, not a large allocation.
 pub fn deserial_string<R: ReadBytesExt>(reader: &mut R, l: usize) -> ParseResult<String> {
-    let mut svec = vec![0; l];
-    reader.read_exact(&mut svec)?;
+    charge_deserial_budget::<u8>(l)?;
+    let mut svec = safe_with_capacity(l);
+    std::io::Read::read_to_end(&mut std::io::Read::take(reader, l as u64), &mut svec)?;
+    if svec.len() != l {
+        bail!(
+            "String of length {} declared, but only {} bytes available.",
+            l,
+            svec.len()
+        )
+    }
     Ok(String::from_utf8(svec)?)
 }
 
@@ -216,6 +317,7 @@ pub fn deserial_vector_no_length<R: ReadBytesExt, T: Deserial>(
     reader: &mut R,
     len: usize,
 ) -> ParseResult<Vec<T>> {
+    charge_deserial_budget::<T>(len)?;
     let mut vec = safe_with_capacity(len);
     for _ in 0..len {
         vec.push(T::deserial(reader)?);
@@ -228,11 +330,59 @@ pub fn deserial_vector_no_length<R: ReadBytesExt, T: Deserial>(
 /// must only be used when the size is bounded, otherwise it will lead to a
 /// memory allocation failure, and panic.
 pub fn deserial_bytes<R: ReadBytesExt>(reader: &mut R, l: usize) -> ParseResult<Vec<u8>> {
+    charge_deserial_budget::<u8>(l)?;
     let mut svec = vec![0; l];
     reader.read_exact(&mut svec)?;
     Ok(svec)
 }
 
+/// Read a value that is framed by its length. The first 4 bytes are taken as
+/// the length of the frame in big endian, and the value is deserialized from
+/// exactly that many bytes that follow. The value may not read beyond the end
+/// of the frame, and it is an error if it does not consume the entire frame.
+pub fn deserial_length_delimited<R: ReadBytesExt, T: Deserial>(source: &mut R) -> ParseResult<T> {
+    let len: u32 = source.get()?;
+    let mut frame = std::io::Read::take(source, u64::from(len));
+    let x = T::deserial(&mut frame).context("Could not deserialize value in the frame.")?;
+    if frame.limit() != 0 {
+        bail!(
+            "Value did not consume the entire frame, {} bytes remaining.",
+            frame.limit()
+        )
+    }
+    Ok(x)
+}
+
+/// A reader that counts the bytes read through it. This can be used to check
+/// how much of the input a [Deserial] implementation consumed.
+pub struct CountingReader<R> {
+    inner:      R,
+    bytes_read: usize,
+}
+
+impl<R: std::io::Read> CountingReader<R> {
+    pub fn new(inner: R) -> Self {
+        CountingReader {
+            inner,
+            bytes_read: 0,
+        }
+    }
+
+    /// The number of bytes read so far.
+    pub fn bytes_read(&self) -> usize { self.bytes_read }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R { self.inner }
+}
+
+impl<R: std::io::Read> std::io::Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read += n;
+        Ok(n)
+    }
+}
+
 impl<T> Deserial for PhantomData<T> {
     #[inline]
     fn deserial<R: ReadBytesExt>(_source: &mut R) -> ParseResult<Self> { Ok(Default::default()) }
@@ -274,13 +424,108 @@ impl Buffer for sha2::Sha256 {
     fn result(self) -> Self::Result { self.finalize().into() }
 }
 
+impl Buffer for sha2::Sha512 {
+    type Result = [u8; 64];
+
+    fn start() -> Self { sha2::Sha512::new() }
+
+    fn result(self) -> Self::Result { self.finalize().into() }
+}
+
+/// Hash the serialization of the value, streaming it directly into the hasher
+/// without an intermediate byte buffer, e.g., `hash_serial::<Sha256, _>(&x)`.
+pub fn hash_serial<D: Buffer, A: Serial>(x: &A) -> D::Result {
+    let mut hasher = D::start();
+    hasher.put(x);
+    hasher.result()
+}
+
 /// Trait implemented by types which can be encoded into byte arrays.
 /// The intention is that the encoding is binary and not human readable.
+///
+/// Implementations are usually derived with the `Serialize` or `Serial` macros
+/// of `crypto_common_derive`. Enums are serialized with a one byte tag, which
+/// can be fixed for each variant with `#[concordium(tag = N)]`.
+///
+/// ```
+/// use crypto_common::*;
+/// use crypto_common_derive::Serialize;
+///
+/// #[derive(Serialize, Debug, PartialEq)]
+/// enum Message {
+///     #[concordium(tag = 2)]
+///     Ping,
+///     #[concordium(tag = 7)]
+///     Pong(u64),
+/// }
+///
+/// assert_eq!(to_bytes(&Message::Pong(1)), [7, 0, 0, 0, 0, 0, 0, 0, 1]);
+/// assert_eq!(
+///     serialize_deserialize(&Message::Ping).ok(),
+///     Some(Message::Ping)
+/// );
+/// ```
+///
+/// Tags that are used twice are rejected,
+///
+/// ```compile_fail
+/// use crypto_common::*;
+/// use crypto_common_derive::Serialize;
+///
+/// #[derive(Serialize, Debug, PartialEq)]
+/// enum Message {
+///     #[concordium(tag = 2)]
+///     Ping,
+///     #[concordium(tag = 2)]
+///     Pong(u64),
+/// }
+/// ```
+///
+/// as are tags that do not fit in a byte.
+///
+/// ```compile_fail
+/// use crypto_common::*;
+/// use crypto_common_derive::Serialize;
+///
+/// #[derive(Serialize, Debug, PartialEq)]
+/// enum Message {
+///     #[concordium(tag = 2)]
+///     Ping,
+///     #[concordium(tag = 256)]
+///     Pong(u64),
+/// }
+/// ```
 pub trait Serial {
     fn serial<B: Buffer>(&self, _out: &mut B);
+
+    /// A lower bound on the number of bytes written by [Serial::serial] for
+    /// any value of the type. Collections use it to estimate their size
+    /// without visiting their elements.
+    const MIN_SERIAL_SIZE: usize = 0;
+
+    /// A lower bound on the number of bytes written by [Serial::serial]. This
+    /// is only used to preallocate buffers, so it need not be exact, but it
+    /// must be computed in constant time. The default is
+    /// [Serial::MIN_SERIAL_SIZE].
+    fn serial_size(&self) -> usize { Self::MIN_SERIAL_SIZE }
+}
+
+/// Trait implemented by types whose serialization always has exactly `N`
+/// bytes. The array returned by `to_array` must be the same as the bytes
+/// produced by [Serial], so [to_bytes] can still be used for these types, but
+/// `to_array` avoids a heap allocation.
+pub trait SerialFixed<const N: usize>: Serial {
+    fn to_array(&self) -> [u8; N];
+}
+
+impl<const N: usize> SerialFixed<N> for [u8; N] {
+    #[inline]
+    fn to_array(&self) -> [u8; N] { *self }
 }
 
 impl Serial for u64 {
+    const MIN_SERIAL_SIZE: usize = 8;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_u64::<BigEndian>(*self)
             .expect("Writing to a buffer should not fail.")
@@ -288,6 +533,8 @@ impl Serial for u64 {
 }
 
 impl Serial for u32 {
+    const MIN_SERIAL_SIZE: usize = 4;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_u32::<BigEndian>(*self)
             .expect("Writing to a buffer should not fail.")
@@ -295,6 +542,8 @@ impl Serial for u32 {
 }
 
 impl Serial for u16 {
+    const MIN_SERIAL_SIZE: usize = 2;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_u16::<BigEndian>(*self)
             .expect("Writing to a buffer should not fail.")
@@ -302,6 +551,8 @@ impl Serial for u16 {
 }
 
 impl Serial for bool {
+    const MIN_SERIAL_SIZE: usize = 1;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         (if *self {
             out.write_u8(1)
@@ -312,7 +563,23 @@ impl Serial for bool {
     }
 }
 
+impl Serial for char {
+    fn serial<B: Buffer>(&self, out: &mut B) { u32::from(*self).serial(out) }
+}
+
+/// Serialize a string by writing its length as a u32 in big endian, followed by
+/// its UTF-8 encoding. Panics if the string is longer than `u32::MAX` bytes.
+impl Serial for String {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        let len = u32::try_from(self.len()).expect("String length exceeds u32::MAX.");
+        len.serial(out);
+        serial_string(self, out)
+    }
+}
+
 impl Serial for u8 {
+    const MIN_SERIAL_SIZE: usize = 1;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_u8(*self)
             .expect("Writing to a buffer should not fail.")
@@ -320,6 +587,8 @@ impl Serial for u8 {
 }
 
 impl Serial for i64 {
+    const MIN_SERIAL_SIZE: usize = 8;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_i64::<BigEndian>(*self)
             .expect("Writing to a buffer should not fail.")
@@ -327,6 +596,8 @@ impl Serial for i64 {
 }
 
 impl Serial for i32 {
+    const MIN_SERIAL_SIZE: usize = 4;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_i32::<BigEndian>(*self)
             .expect("Writing to a buffer should not fail.")
@@ -334,6 +605,8 @@ impl Serial for i32 {
 }
 
 impl Serial for i16 {
+    const MIN_SERIAL_SIZE: usize = 2;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_i16::<BigEndian>(*self)
             .expect("Writing to a buffer should not fail.")
@@ -341,6 +614,8 @@ impl Serial for i16 {
 }
 
 impl Serial for i8 {
+    const MIN_SERIAL_SIZE: usize = 1;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_i8(*self)
             .expect("Writing to a buffer should not fail.")
@@ -420,10 +695,14 @@ impl Serial for std::num::NonZeroI128 {
 /// Serialize a vector by encoding its length as a u64 in big endian and then
 /// the list of elements in sequence.
 impl<T: Serial> Serial for Vec<T> {
+    const MIN_SERIAL_SIZE: usize = 8;
+
     fn serial<B: Buffer>(&self, out: &mut B) {
         (self.len() as u64).serial(out);
         serial_vector_no_length(self, out)
     }
+
+    fn serial_size(&self) -> usize { Self::MIN_SERIAL_SIZE + self.len() * T::MIN_SERIAL_SIZE }
 }
 
 /// Serialize all of the elements in the iterator.
@@ -438,6 +717,40 @@ pub fn serial_vector_no_length<B: Buffer, T: Serial>(xs: &[T], out: &mut B) {
     serial_iter(xs.iter(), out)
 }
 
+/// Write an array prefixed by its length as a big-endian unsigned integer of
+/// `prefix_bytes` bytes, which must be 1, 2, 4, or 8. This is the format of
+/// fields with the `size_length` attribute in the derived implementations.
+/// Like those, this panics if the length does not fit in the prefix, instead
+/// of writing a truncated length.
+pub fn serial_vector_with_prefix<B: Buffer, T: Serial>(xs: &[T], prefix_bytes: u8, out: &mut B) {
+    if ![1, 2, 4, 8].contains(&prefix_bytes) {
+        panic!("Unsupported length prefix of {} bytes.", prefix_bytes);
+    }
+    let len = xs.len() as u64;
+    if prefix_bytes < 8 && len >> (8 * u32::from(prefix_bytes)) != 0 {
+        panic!("The length {} does not fit in {} bytes.", len, prefix_bytes);
+    }
+    out.write_all(&len.to_be_bytes()[8 - usize::from(prefix_bytes)..])
+        .expect("Writing to buffer should succeed.");
+    serial_vector_no_length(xs, out)
+}
+
+/// Read an array written by [serial_vector_with_prefix] with the same
+/// `prefix_bytes`. Fails if `prefix_bytes` is not 1, 2, 4, or 8.
+pub fn deserial_vector_with_prefix<R: ReadBytesExt, T: Deserial>(
+    source: &mut R,
+    prefix_bytes: u8,
+) -> ParseResult<Vec<T>> {
+    let len = match prefix_bytes {
+        1 => u64::from(source.read_u8()?),
+        2 => u64::from(source.read_u16::<BigEndian>()?),
+        4 => u64::from(source.read_u32::<BigEndian>()?),
+        8 => source.read_u64::<BigEndian>()?,
+        _ => bail!("Unsupported length prefix of {} bytes.", prefix_bytes),
+    };
+    deserial_vector_no_length(source, usize::try_from(len)?)
+}
+
 /// Serialize an ordered map. Serialization is by increasing order of keys.
 pub fn serial_map_no_length<B: Buffer, K: Serial, V: Serial>(map: &BTreeMap<K, V>, out: &mut B) {
     for (k, v) in map.iter() {
@@ -449,28 +762,52 @@ pub fn serial_map_no_length<B: Buffer, K: Serial, V: Serial>(map: &BTreeMap<K, V
 
 /// Deserialize a map from a byte source. This ensures there are no duplicates,
 /// as well as that all keys are in strictly increasing order.
-pub fn deserial_map_no_length<R: ReadBytesExt, K: Deserial + Ord + Copy, V: Deserial>(
+pub fn deserial_map_no_length<R: ReadBytesExt, K: Deserial + Ord, V: Deserial>(
     source: &mut R,
     len: usize,
 ) -> ParseResult<BTreeMap<K, V>> {
-    let mut out = BTreeMap::new();
-    let mut x = None;
+    let entries = deserial_sorted_vec_map_no_length(source, len)?;
+    Ok(entries.into_iter().collect())
+}
+
+/// Write a vector of key-value pairs in the same format as a map written by
+/// [serial_map_no_length].
+///
+/// # Panics
+/// If the keys are not in strictly increasing order, since the output could
+/// then not be deserialized.
+pub fn serial_sorted_vec_map_no_length<B: Buffer, K: Serial + Ord, V: Serial>(
+    entries: &[(K, V)],
+    out: &mut B,
+) {
+    assert!(
+        entries.windows(2).all(|w| w[0].0 < w[1].0),
+        "Keys not in order."
+    );
+    for (k, v) in entries.iter() {
+        out.put(k);
+        out.put(v);
+    }
+}
+
+/// Deserialize a map as a vector of key-value pairs, in the order of the keys.
+/// Analogous to [deserial_map_no_length], this ensures there are no
+/// duplicates, and that all the keys are in strictly increasing order.
+pub fn deserial_sorted_vec_map_no_length<R: ReadBytesExt, K: Deserial + Ord, V: Deserial>(
+    source: &mut R,
+    len: usize,
+) -> ParseResult<Vec<(K, V)>> {
+    charge_deserial_budget::<(K, V)>(len)?;
+    let mut out: Vec<(K, V)> = safe_with_capacity(len);
     for _ in 0..len {
-        let k = source.get()?;
+        let k: K = source.get()?;
         let v = source.get()?;
-        match x {
-            None => {
-                out.insert(k, v);
-            }
-            Some(kk) => {
-                if k > kk {
-                    out.insert(k, v);
-                } else {
-                    bail!("Keys not in order.")
-                }
+        if let Some((kk, _)) = out.last() {
+            if k <= *kk {
+                bail!("Keys not in order.")
             }
         }
-        x = Some(k);
+        out.push((k, v));
     }
     Ok(out)
 }
@@ -485,31 +822,29 @@ pub fn serial_set_no_length<B: Buffer, K: Serial>(map: &BTreeSet<K>, out: &mut B
 /// Analogous to [deserial_map_no_length], but for sets.
 /// NB: This ensures there are no duplicates, and that all the keys are in
 /// strictly increasing order.
-pub fn deserial_set_no_length<R: ReadBytesExt, K: Deserial + Ord + Copy>(
+pub fn deserial_set_no_length<R: ReadBytesExt, K: Deserial + Ord + Clone>(
     source: &mut R,
     len: usize,
 ) -> ParseResult<BTreeSet<K>> {
+    charge_deserial_budget::<K>(len)?;
     let mut out = BTreeSet::new();
-    let mut x = None;
+    let mut x: Option<K> = None;
     for _ in 0..len {
-        let k = source.get()?;
-        match x {
-            None => {
-                out.insert(k);
-            }
-            Some(kk) => {
-                if k > kk {
-                    out.insert(k);
-                } else {
-                    bail!("Keys not in order.")
-                }
+        let k: K = source.get()?;
+        if let Some(kk) = x {
+            if k <= kk {
+                bail!("Keys not in order.")
             }
         }
-        x = Some(k);
+        x = Some(k.clone());
+        out.insert(k);
     }
     Ok(out)
 }
 
+/// Tuples are serialized as the concatenation of the serializations of their
+/// components, without any length or separator, e.g., a pair of curve points
+/// is the two point encodings one after the other.
 impl<T: Serial, S: Serial> Serial for (T, S) {
     #[inline]
     fn serial<B: Buffer>(&self, out: &mut B) {
@@ -542,6 +877,8 @@ impl Serial for [u8] {
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_all(self).expect("Writing to buffer is safe.");
     }
+
+    fn serial_size(&self) -> usize { self.len() }
 }
 
 /// Analogue of [Deserial], but instead this has the type to serialize as a type
@@ -581,11 +918,31 @@ impl<A: Deserial + Serial> Serialize for A {}
 /// Directly serialize to a vector of bytes.
 #[inline]
 pub fn to_bytes<A: Serial>(x: &A) -> Vec<u8> {
-    let mut buf = Vec::new();
+    let mut buf = Vec::start_hint(x.serial_size());
     buf.put(x);
     buf
 }
 
+/// Trait for serializing into sinks that may fail, such as files or sockets.
+/// In contrast to [Serial], which assumes that writing to the buffer always
+/// succeeds, errors of the sink are returned instead of causing a panic.
+///
+/// This is implemented for all types that implement [Serial] by first
+/// serializing into a byte vector, which cannot fail, and then writing all the
+/// bytes to the sink. The intermediate vector is needed because [Serial]
+/// implementations write to a [Buffer], which must be constructible with
+/// [Buffer::start] and so cannot borrow the sink, and because they panic on
+/// write errors instead of returning them.
+pub trait TrySerial {
+    fn try_serial<W: std::io::Write>(&self, out: &mut W) -> std::io::Result<()>;
+}
+
+impl<A: Serial> TrySerial for A {
+    fn try_serial<W: std::io::Write>(&self, out: &mut W) -> std::io::Result<()> {
+        out.write_all(&to_bytes(self))
+    }
+}
+
 #[inline]
 /// A small wrapper that is sometimes more convenient than `A::deserial`.
 /// It is here mostly for historical reasons, for backwards compatibility.
@@ -593,6 +950,22 @@ pub fn from_bytes<A: Deserial, R: ReadBytesExt>(source: &mut R) -> ParseResult<A
     A::deserial(source)
 }
 
+/// Deserialize a value from the bytes, and fail unless all of the bytes are
+/// consumed. This should be preferred over [from_bytes] for complete messages,
+/// so that messages with trailing data are rejected.
+pub fn from_bytes_exact<A: Deserial>(bytes: &[u8]) -> ParseResult<A> {
+    let mut source = CountingReader::new(bytes);
+    let x = A::deserial(&mut source)?;
+    let remaining = bytes.len() - source.bytes_read();
+    if remaining != 0 {
+        bail!(
+            "Value did not consume the entire input, {} bytes remaining.",
+            remaining
+        )
+    }
+    Ok(x)
+}
+
 // Some more generic implementations
 
 impl<T: Serial, const N: usize> Serial for [T; N] {
@@ -605,12 +978,33 @@ impl<T: Serial, const N: usize> Serial for [T; N] {
 
 impl<T: Deserial, const N: usize> Deserial for [T; N] {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
-        let mut out_vec = Vec::with_capacity(N);
-        for _ in 0..N {
-            out_vec.push(T::deserial(source)?);
+        // Drops the elements that were read if reading a later one fails, so
+        // that nothing is leaked.
+        struct Guard<T, const N: usize> {
+            arr:  [MaybeUninit<T>; N],
+            init: usize,
+        }
+        impl<T, const N: usize> Drop for Guard<T, N> {
+            fn drop(&mut self) {
+                for x in self.arr[..self.init].iter_mut() {
+                    // Safety: the first `init` elements have been initialized.
+                    unsafe { x.as_mut_ptr().drop_in_place() }
+                }
+            }
         }
-        let out_array: [T; N] = out_vec.try_into().map_err(|_| ()).unwrap();
-        Ok(out_array)
+        let mut guard: Guard<T, N> = Guard {
+            // Safety: an array of MaybeUninit does not require initialization.
+            arr:  unsafe { MaybeUninit::uninit().assume_init() },
+            init: 0,
+        };
+        while guard.init < N {
+            guard.arr[guard.init] = MaybeUninit::new(T::deserial(source)?);
+            guard.init += 1;
+        }
+        // Safety: all N elements are initialized, and the guard is forgotten so
+        // they are not dropped. MaybeUninit<T> has the same layout as T.
+        let guard = std::mem::ManuallyDrop::new(guard);
+        Ok(unsafe { (guard.arr.as_ptr() as *const [T; N]).read() })
     }
 }
 
@@ -686,6 +1080,26 @@ impl Deserial for SocketAddr {
     }
 }
 
+/// Serialized as the number of whole seconds (u64), followed by the number of
+/// nanoseconds in the fractional part (u32).
+impl Serial for std::time::Duration {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        self.as_secs().serial(out);
+        self.subsec_nanos().serial(out);
+    }
+}
+
+impl Deserial for std::time::Duration {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let secs = source.get()?;
+        let nanos: u32 = source.get()?;
+        if nanos >= 1_000_000_000 {
+            bail!("Nanoseconds of a duration must be less than one second.")
+        }
+        Ok(std::time::Duration::new(secs, nanos))
+    }
+}
+
 impl Serial for ExchangeRate {
     fn serial<W: Buffer + WriteBytesExt>(&self, target: &mut W) {
         self.numerator().serial(target);
@@ -758,7 +1172,7 @@ pub fn base16_decode<'de, D: Deserializer<'de>, T: Deserial>(des: D) -> Result<T
         }
 
         fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
-            let bytes = decode(v).map_err(de::Error::custom)?;
+            let bytes = decode_base16(v).map_err(de::Error::custom)?;
             D::deserial(&mut Cursor::new(&bytes)).map_err(de::Error::custom)
         }
     }
@@ -766,13 +1180,28 @@ pub fn base16_decode<'de, D: Deserializer<'de>, T: Deserial>(des: D) -> Result<T
     des.deserialize_str(Base16Visitor(Default::default()))
 }
 
+/// Decode a base 16 string, optionally prefixed by `0x` or `0X`.
+fn decode_base16(x: &str) -> ParseResult<Vec<u8>> {
+    let digits = x
+        .strip_prefix("0x")
+        .or_else(|| x.strip_prefix("0X"))
+        .unwrap_or(x);
+    decode(digits).map_err(|e| match e {
+        hex::FromHexError::OddLength => anyhow::anyhow!(
+            "A base 16 string must have an even number of digits, but it has {}.",
+            digits.len()
+        ),
+        e => e.into(),
+    })
+}
+
 /// Analogous to [base16_encode], but encodes into a string rather than a serde
 /// Serializer.
 pub fn base16_encode_string<S: Serial>(x: &S) -> String { encode(&to_bytes(x)) }
 
 /// Dual to [base16_encode_string].
 pub fn base16_decode_string<S: Deserial>(x: &str) -> ParseResult<S> {
-    let d = decode(x)?;
+    let d = decode_base16(x)?;
     from_bytes(&mut Cursor::new(&d))
 }
 
@@ -815,3 +1244,416 @@ pub fn base16_ignore_length_decode<'de, D: Deserializer<'de>, T: Deserial>(
     }
     des.deserialize_str(Base16IgnoreLengthVisitor(Default::default()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // A writer that fails once more than the given number of bytes is written.
+    struct FailingWriter {
+        remaining: usize,
+    }
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.remaining == 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::WriteZero,
+                    "Writer is full.",
+                ));
+            }
+            let n = cmp::min(self.remaining, buf.len());
+            self.remaining -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+
+    #[test]
+    fn test_try_serial_failing_writer() {
+        let value = (17u64, 3u32);
+        let mut out = FailingWriter { remaining: 12 };
+        assert!(
+            value.try_serial(&mut out).is_ok(),
+            "Writing exactly the capacity should succeed."
+        );
+        let mut out = FailingWriter { remaining: 11 };
+        let err = value
+            .try_serial(&mut out)
+            .expect_err("The error of the writer should be returned.");
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn test_deserial_string() {
+        let bytes = "abcdé".as_bytes();
+        let s = deserial_string(&mut Cursor::new(bytes), bytes.len()).expect("Valid string.");
+        assert_eq!(s, "abcdé");
+        assert!(
+            deserial_string(&mut Cursor::new(bytes), usize::MAX).is_err(),
+            "Length exceeding the data should be rejected."
+        );
+        assert!(
+            deserial_string(&mut Cursor::new(&bytes[..5]), 5).is_err(),
+            "Invalid UTF-8 should be rejected."
+        );
+    }
+
+    #[test]
+    fn test_hash_serial() {
+        let x = (17u64, [1u8, 2, 3], 5u32);
+        let expected: [u8; 32] = sha2::Sha256::digest(to_bytes(&x)).into();
+        assert_eq!(hash_serial::<sha2::Sha256, _>(&x), expected);
+        let expected: [u8; 64] = sha2::Sha512::digest(to_bytes(&x)).into();
+        assert_eq!(hash_serial::<sha2::Sha512, _>(&x), expected);
+    }
+
+    #[test]
+    fn test_char_serialization() {
+        for c in ['a', 'é', '€', '😀'].iter() {
+            assert_eq!(to_bytes(c), (*c as u32).to_be_bytes());
+            let c2: char = from_bytes(&mut Cursor::new(to_bytes(c))).expect("Valid char.");
+            assert_eq!(*c, c2);
+        }
+        // Surrogates and values beyond 0x10FFFF are not Unicode scalar values.
+        for x in [0xD800u32, 0x11_0000].iter() {
+            assert!(from_bytes::<char, _>(&mut Cursor::new(to_bytes(x))).is_err());
+        }
+    }
+
+    #[test]
+    fn test_string_serialization() {
+        let s = String::from("abé");
+        assert_eq!(to_bytes(&s), vec![0, 0, 0, 4, b'a', b'b', 0xc3, 0xa9]);
+        let v = vec![String::new(), s, String::from("😀")];
+        let v2: Vec<String> = from_bytes(&mut Cursor::new(to_bytes(&v))).expect("Valid strings.");
+        assert_eq!(v, v2);
+    }
+
+    #[test]
+    fn test_duration_serialization() {
+        use std::time::Duration;
+        for d in [
+            Duration::from_secs(0),
+            Duration::from_secs(17),
+            Duration::new(3, 999_999_999),
+            Duration::from_nanos(1),
+        ]
+        .iter()
+        {
+            let d2: Duration = from_bytes(&mut Cursor::new(to_bytes(d))).expect("Valid duration.");
+            assert_eq!(*d, d2);
+        }
+        assert_eq!(to_bytes(&Duration::new(1, 2)), [
+            0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 2
+        ]);
+        let mut bytes = to_bytes(&1u64);
+        bytes.extend_from_slice(&to_bytes(&1_000_000_000u32));
+        let res: ParseResult<Duration> = from_bytes(&mut Cursor::new(bytes));
+        assert!(res.is_err(), "Nanoseconds out of range should be rejected.");
+    }
+
+    #[test]
+    fn test_array_deserial() {
+        let arr = [String::from("a"), String::from("bc"), String::new()];
+        let bytes = to_bytes(&arr);
+        let arr2: [String; 3] = from_bytes(&mut Cursor::new(&bytes)).expect("Valid array.");
+        assert_eq!(arr, arr2);
+        // Fails after reading two of the elements, which must be dropped.
+        let res: ParseResult<[String; 3]> = from_bytes(&mut Cursor::new(&bytes[..bytes.len() - 1]));
+        assert!(res.is_err(), "Truncated input should fail.");
+    }
+
+    #[test]
+    fn test_serial_fixed() {
+        use group::CurveProjective;
+        use pairing::bls12_381::{G1, G2};
+        let mut csprng = rand::thread_rng();
+        for _ in 0..100 {
+            let g = G1::random(&mut csprng);
+            let arr: [u8; 48] = g.to_array();
+            assert_eq!(arr[..], to_bytes(&g)[..], "G1 array should match to_bytes.");
+            let arr: [u8; 48] = g.into_affine().to_array();
+            assert_eq!(
+                arr[..],
+                to_bytes(&g)[..],
+                "G1Affine array should match to_bytes."
+            );
+            let h = G2::random(&mut csprng);
+            let arr: [u8; 96] = h.to_array();
+            assert_eq!(arr[..], to_bytes(&h)[..], "G2 array should match to_bytes.");
+            let bytes: [u8; 32] = rand::Rng::gen(&mut csprng);
+            assert_eq!(bytes.to_array()[..], to_bytes(&bytes)[..]);
+        }
+    }
+
+    #[test]
+    fn test_map_non_copy_keys() {
+        let mut map = BTreeMap::new();
+        map.insert(String::from("a"), 1u32);
+        map.insert(String::from("ab"), 2u32);
+        map.insert(String::from("b"), 3u32);
+        let mut bytes = Vec::new();
+        serial_map_no_length(&map, &mut bytes);
+        let map2: BTreeMap<String, u32> =
+            deserial_map_no_length(&mut Cursor::new(&bytes), map.len()).expect("Keys in order.");
+        assert_eq!(map, map2);
+
+        let mut bytes = Vec::new();
+        for (k, v) in map.iter().rev() {
+            bytes.put(k);
+            bytes.put(v);
+        }
+        let res: ParseResult<BTreeMap<String, u32>> =
+            deserial_map_no_length(&mut Cursor::new(&bytes), map.len());
+        assert!(res.is_err(), "Keys out of order should be rejected.");
+
+        let mut bytes = Vec::new();
+        serial_set_no_length(&map.keys().cloned().collect::<BTreeSet<_>>(), &mut bytes);
+        bytes.put(&String::from("b"));
+        let res: ParseResult<BTreeSet<String>> =
+            deserial_set_no_length(&mut Cursor::new(&bytes), map.len() + 1);
+        assert!(res.is_err(), "Duplicate keys should be rejected.");
+    }
+
+    #[test]
+    #[should_panic(expected = "Keys not in order.")]
+    fn test_sorted_vec_map_unordered() {
+        let entries = vec![(2u32, 1u8), (1u32, 2u8)];
+        serial_sorted_vec_map_no_length(&entries, &mut Vec::new());
+    }
+
+    #[test]
+    fn test_base16_decode_prefix() {
+        let expected = 0x0102_03ffu32;
+        for input in ["010203ff", "0x010203ff", "0X010203FF"].iter() {
+            assert_eq!(
+                base16_decode_string::<u32>(input).expect("Valid input."),
+                expected,
+                "Decoding {}.",
+                input
+            );
+            let json = format!("\"{}\"", input);
+            let mut de = serde_json::Deserializer::from_str(&json);
+            let x: u32 = base16_decode(&mut de).expect("Valid JSON input.");
+            assert_eq!(x, expected, "Decoding {} from JSON.", input);
+        }
+        let err = base16_decode_string::<u32>("0x010203f").unwrap_err();
+        assert!(err.to_string().contains("even number of digits"));
+    }
+
+    #[test]
+    fn test_counting_reader() {
+        #[derive(crypto_common_derive::Serialize, Debug, PartialEq)]
+        struct Example {
+            a: u64,
+            b: Vec<u16>,
+            c: bool,
+        }
+        let x = Example {
+            a: 17,
+            b: vec![1, 2, 3],
+            c: true,
+        };
+        let mut bytes = to_bytes(&x);
+        let len = bytes.len();
+        bytes.extend_from_slice(&[1, 2, 3]);
+        let mut source = CountingReader::new(Cursor::new(&bytes));
+        let y: Example = source.get().expect("Deserialization should succeed.");
+        assert_eq!(x, y);
+        assert_eq!(source.bytes_read(), len, "Exactly the value is consumed.");
+        assert_eq!(source.into_inner().position(), len as u64);
+    }
+
+    #[test]
+    fn test_deserial_budget() {
+        // 10 vectors of 10 vectors of 10 bytes.
+        let value = vec![vec![vec![7u8; 10]; 10]; 10];
+        let bytes = to_bytes(&value);
+        let vec_size = std::mem::size_of::<Vec<u8>>();
+        // The collections take up 10 + 100 vectors and 1000 bytes.
+        let needed = 110 * vec_size + 1000;
+        let mut budget = DeserialBudget::new(needed);
+        let parsed: Vec<Vec<Vec<u8>>> = deserial_with_budget(&mut Cursor::new(&bytes), &mut budget)
+            .expect("Exactly within the budget.");
+        assert_eq!(parsed, value);
+        assert_eq!(budget.remaining(), 0);
+        let mut budget = DeserialBudget::new(needed - 1);
+        assert!(deserial_with_budget::<Vec<Vec<Vec<u8>>>, _>(
+            &mut Cursor::new(&bytes),
+            &mut budget
+        )
+        .is_err());
+        // A declared length is charged before the elements are read.
+        let mut budget = DeserialBudget::default();
+        let huge = to_bytes(&(DEFAULT_DESERIAL_BUDGET as u64 + 1));
+        assert!(deserial_with_budget::<Vec<u8>, _>(&mut Cursor::new(&huge), &mut budget).is_err());
+        assert_eq!(budget.remaining(), DEFAULT_DESERIAL_BUDGET);
+        // Outside of deserial_with_budget there is no limit.
+        assert_eq!(
+            from_bytes::<Vec<Vec<Vec<u8>>>, _>(&mut Cursor::new(&bytes)).ok(),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn test_vector_with_prefix() {
+        let xs = vec![1u16, 2, 3];
+        for &prefix_bytes in [1u8, 2, 4, 8].iter() {
+            let mut bytes = Vec::new();
+            serial_vector_with_prefix(&xs, prefix_bytes, &mut bytes);
+            let prefix = usize::from(prefix_bytes);
+            assert_eq!(bytes.len(), prefix + 6);
+            assert_eq!(bytes[prefix - 1], 3, "The length is big endian.");
+            assert!(bytes[..prefix - 1].iter().all(|&b| b == 0));
+            let ys: Vec<u16> = deserial_vector_with_prefix(&mut Cursor::new(&bytes), prefix_bytes)
+                .expect("Deserialization should succeed.");
+            assert_eq!(xs, ys);
+            // A one byte prefix cannot be read as a longer prefix.
+            if prefix_bytes > 1 {
+                let mut short = Vec::new();
+                serial_vector_with_prefix(&xs, 1, &mut short);
+                assert!(deserial_vector_with_prefix::<_, u16>(
+                    &mut Cursor::new(&short),
+                    prefix_bytes
+                )
+                .is_err());
+            }
+        }
+        assert!(deserial_vector_with_prefix::<_, u16>(&mut Cursor::new(&[0u8; 8]), 3).is_err());
+        // The maximal lengths that fit, and the smallest that do not.
+        for &(prefix_bytes, max) in [(1u8, 255usize), (2, 65535)].iter() {
+            let mut bytes = Vec::new();
+            serial_vector_with_prefix(&vec![0u8; max], prefix_bytes, &mut bytes);
+            assert!(bytes[..usize::from(prefix_bytes)].iter().all(|&b| b == 255));
+            assert!(
+                std::panic::catch_unwind(|| {
+                    serial_vector_with_prefix(&vec![0u8; max + 1], prefix_bytes, &mut Vec::new())
+                })
+                .is_err(),
+                "A length that does not fit should not be truncated."
+            );
+        }
+        assert!(
+            std::panic::catch_unwind(|| serial_vector_with_prefix(&[0u8], 3, &mut Vec::new()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_exact() {
+        let bytes = to_bytes(&(17u64, vec![1u16, 2, 3]));
+        let x: (u64, Vec<u16>) = from_bytes_exact(&bytes).expect("Input is exactly the value.");
+        assert_eq!(x, (17, vec![1, 2, 3]));
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(
+            from_bytes_exact::<(u64, Vec<u16>)>(&trailing).is_err(),
+            "Trailing bytes should be rejected."
+        );
+        assert!(
+            from_bytes::<(u64, Vec<u16>), _>(&mut Cursor::new(&trailing)).is_ok(),
+            "from_bytes ignores trailing bytes."
+        );
+        assert!(
+            from_bytes_exact::<(u64, Vec<u16>)>(&bytes[..bytes.len() - 1]).is_err(),
+            "Truncated input should be rejected."
+        );
+    }
+
+    #[test]
+    fn test_length_delimited_exact() {
+        let mut bytes = to_bytes(&8u32);
+        bytes.extend_from_slice(&to_bytes(&17u64));
+        bytes.extend_from_slice(&to_bytes(&3u8));
+        let mut source = Cursor::new(&bytes);
+        let x: u64 = deserial_length_delimited(&mut source).expect("Frame is exactly a u64.");
+        assert_eq!(x, 17);
+        let y: u8 = source
+            .get()
+            .expect("Bytes after the frame should be untouched.");
+        assert_eq!(y, 3);
+    }
+
+    #[test]
+    fn test_length_delimited_under_consume() {
+        // A frame of 9 bytes, but a u64 only consumes 8.
+        let mut bytes = to_bytes(&9u32);
+        bytes.extend_from_slice(&to_bytes(&17u64));
+        bytes.push(0);
+        let res = deserial_length_delimited::<_, u64>(&mut Cursor::new(&bytes));
+        assert!(
+            res.is_err(),
+            "Unconsumed bytes in the frame should be rejected."
+        );
+    }
+
+    #[test]
+    fn test_length_delimited_over_consume() {
+        // A frame of 4 bytes, followed by more data, but a u64 needs 8 bytes.
+        let mut bytes = to_bytes(&4u32);
+        bytes.extend_from_slice(&to_bytes(&17u64));
+        let res = deserial_length_delimited::<_, u64>(&mut Cursor::new(&bytes));
+        assert!(res.is_err(), "Reading beyond the frame should be rejected.");
+    }
+
+    // A buffer that counts how often its storage is reallocated.
+    struct CountingBuffer {
+        data:          Vec<u8>,
+        reallocations: usize,
+    }
+
+    impl std::io::Write for CountingBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let capacity = self.data.capacity();
+            self.data.extend_from_slice(buf);
+            if self.data.capacity() != capacity {
+                self.reallocations += 1;
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+
+    impl Buffer for CountingBuffer {
+        type Result = Vec<u8>;
+
+        fn start() -> Self {
+            CountingBuffer {
+                data:          Vec::new(),
+                reallocations: 0,
+            }
+        }
+
+        fn start_hint(l: usize) -> Self {
+            CountingBuffer {
+                data:          Vec::with_capacity(l),
+                reallocations: 0,
+            }
+        }
+
+        fn result(self) -> Self::Result { self.data }
+    }
+
+    #[test]
+    fn test_serial_size_preallocates() {
+        let v = vec![7u8; 100_000];
+        assert_eq!(v.serial_size(), 8 + v.len());
+        assert_eq!(vec![1u32, 2, 3].serial_size(), 8 + 3 * 4);
+        // Elements of variable size only contribute their lower bound, here
+        // the length prefix.
+        assert_eq!(vec![vec![1u8; 10]; 3].serial_size(), 8 + 3 * 8);
+        let mut buf = CountingBuffer::start_hint(v.serial_size());
+        v.serial(&mut buf);
+        assert_eq!(buf.reallocations, 0, "Hinted buffer should not reallocate.");
+        let mut buf = CountingBuffer::start();
+        v.serial(&mut buf);
+        assert!(buf.reallocations > 0, "Unhinted buffer reallocates.");
+        let bytes = to_bytes(&v);
+        assert_eq!(bytes.len(), 8 + v.len());
+        assert_eq!(bytes.capacity(), bytes.len(), "to_bytes allocates once.");
+    }
+}
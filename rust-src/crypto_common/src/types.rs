@@ -9,9 +9,10 @@ pub use concordium_contracts_common::{AccountAddress, Address, Amount, ACCOUNT_A
 use concordium_contracts_common::{
     ContractAddress, ContractName, OwnedContractName, OwnedReceiveName, ReceiveName,
 };
-use crypto_common_derive::Serialize;
+use crypto_common_derive::{Serial, Serialize};
 use derive_more::{Display, From, FromStr, Into};
 use std::{collections::BTreeMap, num::ParseIntError, str::FromStr};
+use zeroize::Zeroize;
 /// Index of an account key that is to be used.
 #[derive(
     Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Display, From, Into,
@@ -160,6 +161,44 @@ impl Deserial for OwnedContractName {
     }
 }
 
+/// A wrapper that makes a type that only implements the smart contract
+/// serialization traits, [concordium_contracts_common::Serial] and
+/// [concordium_contracts_common::Deserial], usable where [Serial] and
+/// [Deserial] are required. The value is serialized with its smart contract
+/// encoding. Note that this is in general different from the [Serial] encoding
+/// of types that implement both, since, e.g., integers are little endian in
+/// smart contracts, which is why this is not a blanket implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ContractEncoded<T>(pub T);
+
+impl<T: concordium_contracts_common::Serial> Serial for ContractEncoded<T> {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        let mut bytes = Vec::new();
+        self.0
+            .serial(&mut bytes)
+            .expect("Writing to a vector should succeed.");
+        out.write_all(&bytes)
+            .expect("Writing to buffer should succeed.")
+    }
+}
+
+/// Adapter for reading a smart contract value from a [ReadBytesExt] source.
+struct ContractReader<'a, R>(&'a mut R);
+
+impl<R: ReadBytesExt> concordium_contracts_common::Read for ContractReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> concordium_contracts_common::ParseResult<usize> {
+        self.0.read(buf).map_err(|_| Default::default())
+    }
+}
+
+impl<T: concordium_contracts_common::Deserial> Deserial for ContractEncoded<T> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let value = T::deserial(&mut ContractReader(source))
+            .map_err(|_| anyhow::anyhow!("Could not parse the smart contract encoding."))?;
+        Ok(ContractEncoded(value))
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 /// A single signature. Using the same binary and JSON serialization as the
 /// Haskell counterpart. In particular this means encoding the length as 2
@@ -318,8 +357,11 @@ impl FromStr for Timestamp {
 
 /// A ed25519 keypair. This is available in the `ed25519::dalek` crate, but the
 /// JSON serialization there is not compatible with what we use, so we redefine
-/// it there.
-#[derive(Debug, SerdeSerialize, SerdeDeserialize)]
+/// it there. The binary serialization is the 32 bytes of the secret key
+/// followed by the 32 bytes of the public key. Binary deserialization fails if
+/// the public key is not a valid point, or if it is not the public key of the
+/// secret key.
+#[derive(Debug, SerdeSerialize, SerdeDeserialize, Serial)]
 pub struct KeyPair {
     #[serde(
         rename = "signKey",
@@ -361,6 +403,18 @@ impl KeyPair {
     }
 }
 
+impl Deserial for KeyPair {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let secret: ed25519_dalek::SecretKey = source.get()?;
+        let public: ed25519_dalek::PublicKey = source.get()?;
+        let expected = ed25519_dalek::PublicKey::from(&secret);
+        if !crate::ct::ct_eq_bytes(expected.as_bytes(), public.as_bytes()) {
+            anyhow::bail!("Public key does not correspond to the secret key.")
+        }
+        Ok(Self { secret, public })
+    }
+}
+
 impl From<KeyPair> for ed25519_dalek::Keypair {
     fn from(kp: KeyPair) -> ed25519_dalek::Keypair {
         ed25519_dalek::Keypair {
@@ -370,6 +424,42 @@ impl From<KeyPair> for ed25519_dalek::Keypair {
     }
 }
 
+/// A fixed number of secret bytes, e.g., a seed or key material. The bytes are
+/// zeroed when the value is dropped, and are not revealed by the Debug
+/// implementation.
+#[derive(Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct SecretBytes<const N: usize>([u8; N]);
+
+impl<const N: usize> SecretBytes<N> {
+    pub fn new(bytes: [u8; N]) -> Self { SecretBytes(bytes) }
+
+    /// Access the secret bytes.
+    pub fn expose(&self) -> &[u8; N] { &self.0 }
+}
+
+impl<const N: usize> Drop for SecretBytes<N> {
+    fn drop(&mut self) { self.0.zeroize() }
+}
+
+impl<const N: usize> std::fmt::Debug for SecretBytes<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SecretBytes<{}>(<hidden>)", N)
+    }
+}
+
+impl<const N: usize> Serial for SecretBytes<N> {
+    fn serial<B: Buffer>(&self, out: &mut B) { self.0.serial(out) }
+}
+
+impl<const N: usize> Deserial for SecretBytes<N> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let mut out = SecretBytes([0u8; N]);
+        source.read_exact(&mut out.0)?;
+        Ok(out)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -378,6 +468,118 @@ mod tests {
         Rng,
     };
 
+    #[test]
+    fn key_pair_serialization() {
+        let mut csprng = rand::thread_rng();
+        for _ in 0..10 {
+            let kp = KeyPair::generate(&mut csprng);
+            let bytes = crate::to_bytes(&kp);
+            assert_eq!(bytes.len(), 64);
+            assert_eq!(&bytes[..32], kp.secret.as_bytes());
+            assert_eq!(&bytes[32..], kp.public.as_bytes());
+            let kp2 = crate::from_bytes_exact::<KeyPair>(&bytes).expect("Valid key pair.");
+            assert_eq!(kp2.secret.as_bytes(), kp.secret.as_bytes());
+            assert_eq!(kp2.public, kp.public);
+            assert_eq!(
+                crate::serialize_deserialize(&kp.public).expect("Valid public key."),
+                kp.public
+            );
+        }
+        // Find an encoding of a public key that is not a point on the curve.
+        let bad_point = (0u8..=255)
+            .map(|b| [b; 32])
+            .find(|bytes| ed25519_dalek::PublicKey::from_bytes(bytes).is_err())
+            .expect("Some of these are not points.");
+        let mut bytes = crate::to_bytes(&KeyPair::generate(&mut csprng));
+        bytes[32..].copy_from_slice(&bad_point);
+        assert!(crate::from_bytes_exact::<KeyPair>(&bytes).is_err());
+        assert!(crate::from_bytes_exact::<ed25519_dalek::PublicKey>(&bad_point).is_err());
+        // A valid public key of a different secret key.
+        let mut bytes = crate::to_bytes(&KeyPair::generate(&mut csprng));
+        bytes[32..].copy_from_slice(KeyPair::generate(&mut csprng).public.as_bytes());
+        let err = crate::from_bytes_exact::<KeyPair>(&bytes).unwrap_err();
+        assert!(err.to_string().contains("does not correspond"));
+    }
+
+    #[test]
+    fn contract_encoded_roundtrip() {
+        use concordium_contracts_common as contracts;
+        // The contract derive macros generate paths to concordium_std or
+        // concordium_rust_sdk, depending on features, so the traits are
+        // implemented by hand here in the same way as the derive would.
+        #[derive(Debug, PartialEq)]
+        struct State {
+            counter: u32,
+            owner:   AccountAddress,
+            amount:  Amount,
+        }
+        impl contracts::Serial for State {
+            fn serial<W: contracts::Write>(&self, out: &mut W) -> Result<(), W::Err> {
+                contracts::Serial::serial(&self.counter, out)?;
+                contracts::Serial::serial(&self.owner, out)?;
+                contracts::Serial::serial(&self.amount, out)
+            }
+        }
+        impl contracts::Deserial for State {
+            fn deserial<R: contracts::Read>(source: &mut R) -> contracts::ParseResult<Self> {
+                Ok(State {
+                    counter: contracts::Deserial::deserial(source)?,
+                    owner:   contracts::Deserial::deserial(source)?,
+                    amount:  contracts::Deserial::deserial(source)?,
+                })
+            }
+        }
+        let state = ContractEncoded(State {
+            counter: 0x0102_0304,
+            owner:   AccountAddress([7u8; 32]),
+            amount:  Amount::from_micro_ccd(17),
+        });
+        let bytes = crate::to_bytes(&state);
+        assert_eq!(bytes.len(), 4 + 32 + 8);
+        assert_eq!(
+            &bytes[..4],
+            &[4, 3, 2, 1],
+            "The contract encoding is little endian."
+        );
+        let parsed: ContractEncoded<State> =
+            crate::from_bytes(&mut std::io::Cursor::new(&bytes)).expect("Valid bytes.");
+        assert_eq!(parsed, state);
+        assert!(
+            crate::from_bytes::<ContractEncoded<State>, _>(&mut std::io::Cursor::new(&bytes[1..]))
+                .is_err(),
+            "Too few bytes should fail."
+        );
+    }
+
+    #[test]
+    fn secret_bytes_serialization() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let secret = SecretBytes::<32>::new(rng.gen());
+            let bytes = crate::to_bytes(&secret);
+            assert_eq!(&bytes[..], &secret.expose()[..]);
+            let secret2: SecretBytes<32> =
+                crate::from_bytes(&mut std::io::Cursor::new(&bytes)).expect("Valid bytes.");
+            assert_eq!(secret, secret2);
+        }
+        assert!(
+            crate::from_bytes::<SecretBytes<32>, _>(&mut std::io::Cursor::new(&[0u8; 31])).is_err(),
+            "Too few bytes should fail."
+        );
+    }
+
+    #[test]
+    fn secret_bytes_zeroed_on_drop() {
+        let mut slot = std::mem::MaybeUninit::new(SecretBytes::new([0xabu8; 32]));
+        // Safety: the slot is initialized, and it is not used as a SecretBytes
+        // after being dropped, only its memory is inspected.
+        let memory = unsafe {
+            slot.as_mut_ptr().drop_in_place();
+            *(slot.as_ptr() as *const [u8; 32])
+        };
+        assert_eq!(memory, [0u8; 32], "Secret should be zeroed after drop.");
+    }
+
     #[test]
     fn transaction_signature_serialization() {
         let mut rng = rand::thread_rng();
@@ -86,9 +86,23 @@ pub fn serde_base16_ignore_length_serialize_derive(input: TokenStream) -> TokenS
     gen.into()
 }
 
+/// Derive [Deserial](../crypto_common/trait.Deserial.html) for a struct. This
+/// is the inverse of the [Serial](derive.Serial.html) derive, and supports the
+/// same attributes. In addition, fields with a length attribute can be
+/// annotated with `#[concordium(min_len = N, max_len = M)]`, either bound
+/// being optional, in which case deserialization fails if the decoded length
+/// is outside the range `[N, M]`. For a struct with a checksum attribute,
+/// deserialization fails if the checksum does not match. For an enum,
+/// deserialization fails if the tag does not belong to any variant.
 #[proc_macro_derive(
     Deserial,
-    attributes(size_length, map_size_length, set_size_length, string_size_length)
+    attributes(
+        size_length,
+        map_size_length,
+        set_size_length,
+        string_size_length,
+        concordium
+    )
 )]
 pub fn deserial_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).expect("Cannot parse input.");
@@ -119,6 +133,255 @@ fn find_length_attribute(l: &[syn::Attribute], attr: &str) -> Option<u32> {
     None
 }
 
+/// Find the value of the `#[concordium(key = N)]` attribute, if present. The
+/// supported keys are `order`, `min_len`, `max_len`, and `tag`, and several of
+/// them can be given in the same attribute, e.g., `#[concordium(min_len = 1,
+/// max_len = 10)]`.
+fn find_concordium_attribute(l: &[syn::Attribute], key: &str) -> Option<usize> {
+    let mut result = None;
+    for attr in l.iter() {
+        if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+            if !list.path.is_ident("concordium") {
+                continue;
+            }
+            for nested in list.nested.iter() {
+                match nested {
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(mn))
+                        if ["order", "min_len", "max_len", "tag"]
+                            .iter()
+                            .any(|k| mn.path.is_ident(k)) =>
+                    {
+                        if !mn.path.is_ident(key) {
+                            continue;
+                        }
+                        let value = match &mn.lit {
+                            syn::Lit::Int(int) => int.base10_parse().ok(),
+                            _ => None,
+                        };
+                        match value {
+                            Some(v) if result.is_none() => result = Some(v),
+                            Some(_) => panic!("Duplicate attribute {}.", key),
+                            None => panic!("The value of {} must be a non-negative integer.", key),
+                        }
+                    }
+                    syn::NestedMeta::Meta(syn::Meta::Path(p))
+                        if p.is_ident("map_from_sorted_vec") => {}
+                    nested => panic!("Unknown attribute {}.", quote!(#nested)),
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Whether the field is annotated with `#[concordium(map_from_sorted_vec)]`,
+/// in which case a `Vec<(K, V)>` with keys in strictly increasing order is
+/// serialized as a map. This requires the `map_size_length` attribute.
+fn find_map_from_sorted_vec_attribute(l: &[syn::Attribute]) -> bool {
+    let found = l.iter().any(|attr| match attr.parse_meta() {
+        Ok(syn::Meta::List(list)) if list.path.is_ident("concordium") => {
+            list.nested.iter().any(|nested| {
+                matches!(nested, syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("map_from_sorted_vec"))
+            })
+        }
+        _ => false,
+    });
+    if found && find_length_attribute(l, "map_size_length").is_none() {
+        panic!("map_from_sorted_vec requires the map_size_length attribute.");
+    }
+    found
+}
+
+/// The functions that serialize and deserialize the entries of a map field,
+/// depending on whether it is a `BTreeMap` or a sorted `Vec`.
+fn map_functions(l: &[syn::Attribute]) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    if find_map_from_sorted_vec_attribute(l) {
+        (
+            quote!(crypto_common::serial_sorted_vec_map_no_length),
+            quote!(crypto_common::deserial_sorted_vec_map_no_length),
+        )
+    } else {
+        (
+            quote!(crypto_common::serial_map_no_length),
+            quote!(crypto_common::deserial_map_no_length),
+        )
+    }
+}
+
+/// Find the value of the `#[concordium(order = N)]` attribute, if present.
+fn find_order_attribute(l: &[syn::Attribute]) -> Option<usize> {
+    find_concordium_attribute(l, "order")
+}
+
+/// Generate a check that the decoded length `len` of a collection lies within
+/// the bounds given by `#[concordium(min_len = N, max_len = M)]`, if any.
+fn length_bounds_check(l: &[syn::Attribute]) -> proc_macro2::TokenStream {
+    let min = find_concordium_attribute(l, "min_len");
+    let max = find_concordium_attribute(l, "max_len");
+    let mut tokens = proc_macro2::TokenStream::new();
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            panic!("min_len {} exceeds max_len {}.", min, max);
+        }
+    }
+    if let Some(min) = min {
+        tokens.extend(quote! {
+            if len < #min {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Length {} is below the minimum {}.", len, #min),
+                ).into());
+            }
+        });
+    }
+    if let Some(max) = max {
+        tokens.extend(quote! {
+            if len > #max {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Length {} is above the maximum {}.", len, #max),
+                ).into());
+            }
+        });
+    }
+    tokens
+}
+
+/// Generate the serialization of the length of the collection `field` as an
+/// unsigned integer of `l` bytes. The generated code panics if the length does
+/// not fit, naming the field `name` in the message.
+fn serial_length_prefix(
+    l: u32,
+    field: &proc_macro2::TokenStream,
+    name: &syn::Ident,
+    out: &syn::Ident,
+) -> proc_macro2::TokenStream {
+    let id = format_ident!("u{}", 8 * l);
+    quote! {
+        let len: #id = std::convert::TryFrom::try_from(#field.len()).expect(concat!(
+            "The length of ",
+            stringify!(#name),
+            " does not fit in the length prefix."
+        ));
+        len.serial(#out);
+    }
+}
+
+/// Return the indices of the fields in the order in which they are serialized.
+/// This is the declaration order, unless the fields are annotated with
+/// `#[concordium(order = N)]`, in which case all fields must be annotated and
+/// the orders must be exactly 0, 1, ..., n-1 in some arrangement.
+fn serialization_order(fields: &syn::Fields) -> Vec<usize> {
+    let orders = fields
+        .iter()
+        .map(|f| find_order_attribute(&f.attrs))
+        .collect::<Vec<_>>();
+    if orders.iter().all(Option::is_none) {
+        return (0..orders.len()).collect();
+    }
+    let mut indices = vec![None; orders.len()];
+    for (i, order) in orders.into_iter().enumerate() {
+        let order = order.expect("Either all or no fields must have an explicit order.");
+        match indices.get_mut(order) {
+            Some(slot @ None) => *slot = Some(i),
+            Some(Some(_)) => panic!("Duplicate field order {}.", order),
+            None => panic!(
+                "Field orders must be contiguous from 0, but {} is out of range.",
+                order
+            ),
+        }
+    }
+    indices
+        .into_iter()
+        .map(|i| i.expect("All slots are filled."))
+        .collect()
+}
+
+/// Find the checksum algorithm given by the container attribute
+/// `#[concordium(checksum = "...")]`, if present. Currently only `"crc32"` is
+/// supported.
+fn find_checksum_attribute(l: &[syn::Attribute]) -> Option<String> {
+    let mut result = None;
+    for attr in l.iter() {
+        if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+            if !list.path.is_ident("concordium") {
+                continue;
+            }
+            for nested in list.nested.iter() {
+                match nested {
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(mn))
+                        if mn.path.is_ident("checksum") =>
+                    {
+                        let value = match &mn.lit {
+                            syn::Lit::Str(s) => s.value(),
+                            _ => panic!("The value of checksum must be a string."),
+                        };
+                        if value != "crc32" {
+                            panic!("Unsupported checksum algorithm {}.", value);
+                        }
+                        if result.replace(value).is_some() {
+                            panic!("Duplicate attribute checksum.");
+                        }
+                    }
+                    nested => panic!("Unknown attribute {}.", quote!(#nested)),
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Wrap the generated deserialization of the fields, `body`, which evaluates to
+/// the deserialized value, so that the checksum following the fields is read
+/// and checked, if the struct has a checksum attribute.
+fn deserial_checksum(
+    l: &[syn::Attribute],
+    source: &syn::Ident,
+    body: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if find_checksum_attribute(l).is_none() {
+        return quote!(Ok({ #body }));
+    }
+    quote! {
+        let mut checksum_reader = crypto_common::checksum::Crc32Reader::new(&mut *#source);
+        let value = {
+            let #source = &mut checksum_reader;
+            #body
+        };
+        let computed = checksum_reader.checksum();
+        let checksum = u32::deserial(#source)?;
+        if checksum != computed {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Checksum mismatch: expected {:08x}, computed {:08x}.", checksum, computed),
+            ).into());
+        }
+        Ok(value)
+    }
+}
+
+/// Wrap the generated serialization of the fields, `body`, so that it is
+/// followed by the checksum of the serialized fields, if the struct has a
+/// checksum attribute.
+fn serial_checksum(
+    l: &[syn::Attribute],
+    out: &syn::Ident,
+    body: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if find_checksum_attribute(l).is_none() {
+        return body;
+    }
+    quote! {
+        let mut checksum_body = Vec::<u8>::new();
+        {
+            let #out = &mut checksum_body;
+            #body
+        }
+        std::io::Write::write_all(#out, &checksum_body).expect("Writing to a buffer should not fail.");
+        crypto_common::checksum::crc32(&checksum_body).serial(#out);
+    }
+}
+
 fn impl_deserial(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
 
@@ -133,20 +396,26 @@ fn impl_deserial(ast: &syn::DeriveInput) -> TokenStream {
         let mut names = proc_macro2::TokenStream::new();
         let source = format_ident!("source");
         let mut pusher = |f: &syn::Field, ident| {
+            let check = length_bounds_check(&f.attrs);
             if let Some(l) = find_length_attribute(&f.attrs, "size_length") {
                 let id = format_ident!("u{}", 8 * l);
                 tokens.extend(quote! {
                     let #ident = {
                         let len: #id = #id::deserial(#source)?;
-                        crypto_common::deserial_vector_no_length(#source, usize::try_from(len)?)?
+                        let len = usize::try_from(len)?;
+                        #check
+                        crypto_common::deserial_vector_no_length(#source, len)?
                     };
                 });
             } else if let Some(l) = find_length_attribute(&f.attrs, "map_size_length") {
                 let id = format_ident!("u{}", 8 * l);
+                let (_, deserial_map) = map_functions(&f.attrs);
                 tokens.extend(quote! {
                     let #ident = {
                         let len: #id = #id::deserial(#source)?;
-                        crypto_common::deserial_map_no_length(#source, usize::try_from(len)?)?
+                        let len = usize::try_from(len)?;
+                        #check
+                        #deserial_map(#source, len)?
                     };
                 });
             } else if let Some(l) = find_length_attribute(&f.attrs, "set_size_length") {
@@ -154,7 +423,9 @@ fn impl_deserial(ast: &syn::DeriveInput) -> TokenStream {
                 tokens.extend(quote! {
                     let #ident = {
                         let len: #id = #id::deserial(#source)?;
-                        crypto_common::deserial_set_no_length(#source, usize::try_from(len)?)?
+                        let len = usize::try_from(len)?;
+                        #check
+                        crypto_common::deserial_set_no_length(#source, len)?
                     };
                 });
             } else if let Some(l) = find_length_attribute(&f.attrs, "string_size_length") {
@@ -162,10 +433,15 @@ fn impl_deserial(ast: &syn::DeriveInput) -> TokenStream {
                 tokens.extend(quote! {
                     let #ident = {
                         let len: #id = #id::deserial(#source)?;
-                        crypto_common::deserial_string(#source, usize::try_from(len)?)?
+                        let len = usize::try_from(len)?;
+                        #check
+                        crypto_common::deserial_string(#source, len)?
                     };
                 });
             } else {
+                if !check.is_empty() {
+                    panic!("min_len and max_len require a length attribute on the field.");
+                }
                 let ty = &f.ty;
                 tokens.extend(quote! {
                     let #ident = <#ty as Deserial>::deserial(#source)?;
@@ -175,34 +451,44 @@ fn impl_deserial(ast: &syn::DeriveInput) -> TokenStream {
         };
         let gen = match data.fields {
             syn::Fields::Named(_) => {
-                for f in data.fields.iter() {
-                    let ident = f.ident.clone().unwrap(); // safe since named fields.
-                    pusher(f, ident);
+                let fields = data.fields.iter().collect::<Vec<_>>();
+                for i in serialization_order(&data.fields) {
+                    let ident = fields[i].ident.clone().unwrap(); // safe since named fields.
+                    pusher(fields[i], ident);
                 }
+                let body = deserial_checksum(&ast.attrs, &source, quote! {
+                    #tokens
+                    #name{#names}
+                });
                 quote! {
                     #[automatically_derived]
                     impl #impl_generics Deserial for #name #ty_generics #where_clauses {
                         #[allow(non_snake_case)]
                         fn deserial<#ident: ReadBytesExt>(#source: &mut #ident) -> ParseResult<Self> {
                             use std::convert::TryFrom;
-                            #tokens
-                            Ok(#name{#names})
+                            #body
                         }
                     }
                 }
             }
             syn::Fields::Unnamed(_) => {
-                for (i, f) in data.fields.iter().enumerate() {
+                let fields = data.fields.iter().collect::<Vec<_>>();
+                for i in serialization_order(&data.fields) {
                     let ident = format_ident!("x_{}", i);
-                    pusher(f, ident);
+                    pusher(fields[i], ident);
                 }
+                // The constructor takes the fields in declaration order.
+                let names = (0..fields.len()).map(|i| format_ident!("x_{}", i));
+                let body = deserial_checksum(&ast.attrs, &source, quote! {
+                    #tokens
+                    #name(#(#names,)*)
+                });
                 quote! {
                     #[automatically_derived]
                     impl #impl_generics Deserial for #name #ty_generics #where_clauses {
                         fn deserial<#ident: ReadBytesExt>(#source: &mut #ident) -> ParseResult<Self> {
                             use std::convert::TryFrom;
-                            #tokens
-                            Ok(#name(#names))
+                            #body
                         }
                     }
                 }
@@ -210,14 +496,61 @@ fn impl_deserial(ast: &syn::DeriveInput) -> TokenStream {
             _ => panic!("#[derive(Deserial)] not implemented for empty structs."),
         };
         gen.into()
+    } else if let syn::Data::Enum(ref data) = ast.data {
+        impl_deserial_enum(ast, data)
     } else {
-        panic!("#[derive(Deserial)] only implemented for structs.")
+        panic!("#[derive(Deserial)] only implemented for structs and enums.")
     }
 }
 
+/// Derive [Serial](../crypto_common/trait.Serial.html) for a struct by
+/// serializing the fields in order. Fields of variable length can be annotated
+/// with one of the attributes `size_length`, `map_size_length`,
+/// `set_size_length`, or `string_size_length`, with a value of 1, 2, 4, or 8.
+/// The field is then prefixed by its length (the number of elements, or the
+/// number of bytes of the UTF-8 encoding in case of strings) as an unsigned
+/// integer of that many bytes. As all integers, the length is written in big
+/// endian. Serialization panics if the length does not fit in the chosen
+/// number of bytes, e.g., a map with 256 entries and `map_size_length = 1`,
+/// instead of writing a truncated length.
+///
+/// The order in which fields are serialized can be decoupled from the order of
+/// declaration by annotating every field with `#[concordium(order = N)]`,
+/// where the orders are 0, 1, ..., n-1 in some arrangement.
+///
+/// A field of type `Vec<(K, V)>` with the `map_size_length` attribute can be
+/// annotated with `#[concordium(map_from_sorted_vec)]`, in which case it is
+/// serialized exactly like a `BTreeMap<K, V>`. The keys must be in strictly
+/// increasing order. This is only checked in debug builds when serializing,
+/// but always when deserializing.
+///
+/// Fields of type `PhantomData<T>` are serialized as zero bytes, and no bounds
+/// are added on the type parameters, so structs with phantom type parameters
+/// can derive this even if the parameters themselves are not serializable.
+///
+/// A struct annotated with `#[concordium(checksum = "crc32")]` is serialized
+/// with a 4-byte CRC-32 checksum of the serialized fields appended, which is
+/// checked on deserialization. This detects accidental corruption, not
+/// tampering. Currently `"crc32"` is the only supported algorithm.
+///
+/// Enums are serialized as a one byte tag followed by the fields of the
+/// variant. By default the tag is the index of the variant in the declaration.
+/// To keep the format stable when variants are reordered, every variant can
+/// instead be annotated with `#[concordium(tag = N)]`, where the tags must be
+/// distinct and at most 255. Fields of enum variants cannot have length or
+/// order attributes.
+///
+/// The documentation of the `Serial` trait in `crypto_common` has examples of
+/// explicit tags, including ones that are rejected.
 #[proc_macro_derive(
     Serial,
-    attributes(size_length, map_size_length, set_size_length, string_size_length)
+    attributes(
+        size_length,
+        map_size_length,
+        set_size_length,
+        string_size_length,
+        concordium
+    )
 )]
 pub fn serial_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).expect("Cannot parse input.");
@@ -238,34 +571,29 @@ fn impl_serial(ast: &syn::DeriveInput) -> TokenStream {
         let gen = match data.fields {
             syn::Fields::Named(_) => {
                 let mut body = proc_macro2::TokenStream::new();
-                for f in data.fields.iter() {
+                let fields = data.fields.iter().collect::<Vec<_>>();
+                for i in serialization_order(&data.fields) {
+                    let f = fields[i];
                     let ident = f.ident.clone().unwrap(); // safe since named fields.
                     if let Some(l) = find_length_attribute(&f.attrs, "size_length") {
-                        let id = format_ident!("u{}", 8 * l);
+                        body.extend(serial_length_prefix(l, &quote!(self.#ident), &ident, &out));
                         body.extend(quote! {
-                            let len: #id = self.#ident.len() as #id;
-                            len.serial(#out);
                             crypto_common::serial_vector_no_length(&self.#ident, #out);
                         });
                     } else if let Some(l) = find_length_attribute(&f.attrs, "map_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
+                        let (serial_map, _) = map_functions(&f.attrs);
+                        body.extend(serial_length_prefix(l, &quote!(self.#ident), &ident, &out));
                         body.extend(quote! {
-                            let len: #id = self.#ident.len() as #id;
-                            len.serial(#out);
-                            crypto_common::serial_map_no_length(&self.#ident, #out);
+                            #serial_map(&self.#ident, #out);
                         })
                     } else if let Some(l) = find_length_attribute(&f.attrs, "set_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
+                        body.extend(serial_length_prefix(l, &quote!(self.#ident), &ident, &out));
                         body.extend(quote! {
-                            let len: #id = self.#ident.len() as #id;
-                            len.serial(#out);
                             crypto_common::serial_set_no_length(&self.#ident, #out);
                         })
                     } else if let Some(l) = find_length_attribute(&f.attrs, "string_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
+                        body.extend(serial_length_prefix(l, &quote!(self.#ident), &ident, &out));
                         body.extend(quote! {
-                            let len: #id = self.#ident.len() as #id;
-                            len.serial(#out);
                             crypto_common::serial_string(self.#ident.as_str(), #out);
                         })
                     } else {
@@ -274,6 +602,7 @@ fn impl_serial(ast: &syn::DeriveInput) -> TokenStream {
                         });
                     }
                 }
+                let body = serial_checksum(&ast.attrs, &out, body);
                 quote! {
                     #[automatically_derived]
                     impl #impl_generics Serial for #name #ty_generics #where_clauses {
@@ -287,53 +616,45 @@ fn impl_serial(ast: &syn::DeriveInput) -> TokenStream {
             syn::Fields::Unnamed(_) => {
                 // this is a hack because I don't know how to generate tuple access expressions
                 // easily
-                let mut names = proc_macro2::TokenStream::new();
                 let mut body = proc_macro2::TokenStream::new();
-                for (i, f) in data.fields.iter().enumerate() {
+                let fields = data.fields.iter().collect::<Vec<_>>();
+                for i in serialization_order(&data.fields) {
+                    let f = fields[i];
                     let ident = format_ident!("x_{}", i);
 
                     if let Some(l) = find_length_attribute(&f.attrs, "size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        let len_ident = format_ident!("len_{}", i);
+                        body.extend(serial_length_prefix(l, &quote!(#ident), &ident, &out));
                         body.extend(quote! {
-                            let #len_ident: #id = #ident.len() as #id;
-                            #len_ident.serial(#out);
-                            serial_vector_no_length(#ident, #out);
+                            crypto_common::serial_vector_no_length(#ident, #out);
                         });
                     } else if let Some(l) = find_length_attribute(&f.attrs, "map_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        let len_ident = format_ident!("len_{}", i);
+                        let (serial_map, _) = map_functions(&f.attrs);
+                        body.extend(serial_length_prefix(l, &quote!(#ident), &ident, &out));
                         body.extend(quote! {
-                            let #len_ident: #id = #ident.len() as #id;
-                            #len_ident.serial(#out);
-                            serial_map_no_length(&self.#ident, #out);
+                            #serial_map(#ident, #out);
                         })
                     } else if let Some(l) = find_length_attribute(&f.attrs, "set_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        let len_ident = format_ident!("len_{}", i);
+                        body.extend(serial_length_prefix(l, &quote!(#ident), &ident, &out));
                         body.extend(quote! {
-                            let #len_ident: #id = #ident.len() as #id;
-                            #len_ident.serial(#out);
-                            serial_set_no_length(&self.#ident, #out);
+                            crypto_common::serial_set_no_length(#ident, #out);
                         })
                     } else if let Some(l) = find_length_attribute(&f.attrs, "string_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        let len_ident = format_ident!("len_{}", i);
+                        body.extend(serial_length_prefix(l, &quote!(#ident), &ident, &out));
                         body.extend(quote! {
-                            let #len_ident: #id = #ident.len() as #id;
-                            #len_ident.serial(#out);
-                            serial_string(self.#ident.as_str(), #out);
+                            crypto_common::serial_string(#ident.as_str(), #out);
                         })
                     } else {
                         body.extend(quote!(#ident.serial(#out);));
                     }
-                    names.extend(quote!(ref #ident,))
                 }
+                // The pattern binds the fields in declaration order.
+                let names = (0..fields.len()).map(|i| format_ident!("x_{}", i));
+                let body = serial_checksum(&ast.attrs, &out, body);
                 quote! {
                     #[automatically_derived]
                     impl #impl_generics Serial for #name #ty_generics #where_clauses {
                         fn serial<#ident: Buffer>(&self, #out: &mut #ident) {
-                            let #name( #names ) = self;
+                            let #name( #(ref #names,)* ) = self;
                             #body
                         }
                     }
@@ -342,14 +663,171 @@ fn impl_serial(ast: &syn::DeriveInput) -> TokenStream {
             _ => panic!("#[derive(Deserial)] not implemented for empty structs."),
         };
         gen.into()
+    } else if let syn::Data::Enum(ref data) = ast.data {
+        impl_serial_enum(ast, data)
     } else {
-        panic!("#[derive(Deserial)] only implemented for structs.")
+        panic!("#[derive(Serial)] only implemented for structs and enums.")
+    }
+}
+
+/// Return the tags of the variants of an enum, in declaration order. These are
+/// the indices of the variants, unless the variants are annotated with
+/// `#[concordium(tag = N)]`, in which case all variants must be annotated and
+/// the tags must be distinct and fit in a `u8`.
+fn variant_tags(data: &syn::DataEnum) -> Vec<u8> {
+    let tags = data
+        .variants
+        .iter()
+        .map(|v| find_concordium_attribute(&v.attrs, "tag"))
+        .collect::<Vec<_>>();
+    if tags.is_empty() {
+        panic!("Enums without variants cannot be serialized.");
+    }
+    if tags.iter().all(Option::is_none) {
+        if tags.len() > 256 {
+            panic!("Enums with more than 256 variants are not supported.");
+        }
+        return (0..tags.len()).map(|i| i as u8).collect();
+    }
+    let mut seen = std::collections::BTreeSet::new();
+    tags.into_iter()
+        .map(|tag| {
+            let tag = tag.expect("Either all or no variants must have an explicit tag.");
+            if tag > 255 {
+                panic!("Tag {} does not fit in a u8.", tag);
+            }
+            if !seen.insert(tag) {
+                panic!("Duplicate variant tag {}.", tag);
+            }
+            tag as u8
+        })
+        .collect()
+}
+
+/// Return the identifiers that the fields of a variant are bound to when
+/// matching on, or constructing, the variant. These are `x_0`, `x_1`, ... for
+/// both named and unnamed fields, so that they cannot shadow other variables.
+fn variant_bindings(fields: &syn::Fields) -> Vec<syn::Ident> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            for attr in [
+                "size_length",
+                "map_size_length",
+                "set_size_length",
+                "string_size_length",
+            ]
+            .iter()
+            {
+                if find_length_attribute(&f.attrs, attr).is_some() {
+                    panic!("Length attributes are not supported on fields of enum variants.");
+                }
+            }
+            if find_concordium_attribute(&f.attrs, "order").is_some() {
+                panic!("Field order is not supported on fields of enum variants.");
+            }
+            format_ident!("x_{}", i)
+        })
+        .collect()
+}
+
+/// The pattern, or constructor, of the variant with its fields bound to the
+/// given identifiers.
+fn variant_pattern(
+    name: &syn::Ident,
+    variant: &syn::Variant,
+    bindings: &[syn::Ident],
+) -> proc_macro2::TokenStream {
+    let variant_ident = &variant.ident;
+    match variant.fields {
+        syn::Fields::Named(_) => {
+            let field_names = variant.fields.iter().map(|f| &f.ident);
+            quote!(#name::#variant_ident { #(#field_names: #bindings,)* })
+        }
+        syn::Fields::Unnamed(_) => quote!(#name::#variant_ident ( #(#bindings,)* )),
+        syn::Fields::Unit => quote!(#name::#variant_ident),
     }
 }
 
+fn impl_deserial_enum(ast: &syn::DeriveInput, data: &syn::DataEnum) -> TokenStream {
+    let name = &ast.ident;
+    let ident = format_ident!("GenericReaderType", span = ast.span());
+    let (impl_generics, ty_generics, where_clauses) = ast.generics.split_for_impl();
+    let source = format_ident!("source");
+    let mut arms = proc_macro2::TokenStream::new();
+    for (variant, tag) in data.variants.iter().zip(variant_tags(data)) {
+        let bindings = variant_bindings(&variant.fields);
+        let tys = variant.fields.iter().map(|f| &f.ty);
+        let pattern = variant_pattern(name, variant, &bindings);
+        arms.extend(quote! {
+            #tag => {
+                #(let #bindings = <#tys as Deserial>::deserial(#source)?;)*
+                #pattern
+            }
+        });
+    }
+    let body = deserial_checksum(&ast.attrs, &source, quote! {
+        match u8::deserial(#source)? {
+            #arms
+            tag => return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Unknown variant tag {}.", tag),
+            ).into()),
+        }
+    });
+    let gen = quote! {
+        #[automatically_derived]
+        impl #impl_generics Deserial for #name #ty_generics #where_clauses {
+            fn deserial<#ident: ReadBytesExt>(#source: &mut #ident) -> ParseResult<Self> {
+                #body
+            }
+        }
+    };
+    gen.into()
+}
+
+fn impl_serial_enum(ast: &syn::DeriveInput, data: &syn::DataEnum) -> TokenStream {
+    let name = &ast.ident;
+    let ident = format_ident!("GenericBufferType", span = ast.span());
+    let (impl_generics, ty_generics, where_clauses) = ast.generics.split_for_impl();
+    let out = format_ident!("out");
+    let mut arms = proc_macro2::TokenStream::new();
+    for (variant, tag) in data.variants.iter().zip(variant_tags(data)) {
+        let bindings = variant_bindings(&variant.fields);
+        let pattern = variant_pattern(name, variant, &bindings);
+        arms.extend(quote! {
+            #pattern => {
+                #tag.serial(#out);
+                #(#bindings.serial(#out);)*
+            }
+        });
+    }
+    let body = serial_checksum(&ast.attrs, &out, quote! {
+        match self {
+            #arms
+        }
+    });
+    let gen = quote! {
+        #[automatically_derived]
+        impl #impl_generics Serial for #name #ty_generics #where_clauses {
+            fn serial<#ident: Buffer>(&self, #out: &mut #ident) {
+                #body
+            }
+        }
+    };
+    gen.into()
+}
+
 #[proc_macro_derive(
     Serialize,
-    attributes(size_length, map_size_length, set_size_length, string_size_length)
+    attributes(
+        size_length,
+        map_size_length,
+        set_size_length,
+        string_size_length,
+        concordium
+    )
 )]
 pub fn serialize_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).expect("Cannot parse input.");
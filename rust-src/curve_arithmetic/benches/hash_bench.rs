@@ -4,7 +4,7 @@ extern crate curve_arithmetic;
 
 use criterion::Criterion;
 use curve_arithmetic::*;
-use pairing::bls12_381::G1;
+use pairing::bls12_381::{G1, G2};
 use rand::*;
 
 macro_rules! rand_m_of_length {
@@ -20,7 +20,9 @@ macro_rules! rand_m_of_length {
 pub fn bench_hash_to_curve(c: &mut Criterion) {
     let mut csprng = thread_rng();
     let msg = rand_m_of_length!(1000, csprng);
+    let msg_g2 = msg.clone();
     c.bench_function("hash_to_g1", move |b| b.iter(|| G1::hash_to_group(&msg)));
+    c.bench_function("hash_to_g2", move |b| b.iter(|| G2::hash_to_group(&msg_g2)));
 }
 
 // To run this benches do the following:
@@ -1,3 +1,4 @@
+use crate::curve_arithmetic::Curve;
 use ff::{Field, PrimeField};
 use group::{CurveProjective, EncodedPoint};
 use pairing::bls12_381::{Fq, FqRepr, G1Uncompressed, G1};
@@ -489,7 +490,7 @@ pub(crate) const K4: [[u64; 6]; 16] = [
 ///    1. u = hash_to_field(msg, 2)
 ///    2. Q0 = map_to_curve(u[0])
 ///    3. Q1 = map_to_curve(u[1])
-///    4. R = Q0 + Q1              
+///    4. R = Q0 + Q1
 ///    5. P = clear_cofactor(R) = h_eff * R   # Clearing cofactor
 ///    6. return P,
 /// where the choices of hash_to_field, map_to_curve and h_eff are as described in https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-8.8.1.
@@ -531,84 +532,94 @@ fn hash_to_field(msg: &[u8], dst: &[u8]) -> (Fq, Fq) {
 
 // Interpret input as integers (big endian)
 // Return (left*2^256 + right) as Fq
-fn fq_from_bytes(left_bytes: &[u8; 32], right_bytes: &[u8; 32]) -> Fq {
-    fn le_u64s_from_be_bytes(bytes: &[u8; 32]) -> Fq {
-        let mut digits = [0u64; 6];
-
-        for (place, chunk) in digits.iter_mut().zip(bytes.chunks(8).rev()) {
-            *place = u64::from_be_bytes(chunk.try_into().expect("Chunk Sie is always 8"))
-        }
-
-        Fq::from_repr(FqRepr(digits)).expect("Only the leading 4 u64s are initialized")
-    }
-
-    let two_to_256_fqrepr = [0u64, 0, 0, 0, 1, 0]; // 2^256
-    let two_to_256_fq = Fq::from_repr(FqRepr(two_to_256_fqrepr)).expect("2^256 fits in modulus");
-
-    let mut left_fq = le_u64s_from_be_bytes(left_bytes);
-    let right_fq = le_u64s_from_be_bytes(right_bytes);
-    left_fq.mul_assign(&two_to_256_fq); // u_0[..32] * 2^256
-    left_fq.add_assign(&right_fq); // u_0[32..] + u_0[32..] * 2^256 = u_0
+pub(crate) fn fq_from_bytes(left_bytes: &[u8; 32], right_bytes: &[u8; 32]) -> Fq {
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(left_bytes);
+    bytes[32..].copy_from_slice(right_bytes);
+    G1::base_from_be_bytes(&bytes).expect("Input is not empty.")
+}
 
-    left_fq
+/// Output size of SHA-256 in bytes, `b_in_bytes` in the specification.
+const XMD_B_IN_BYTES: usize = 32;
+
+/// Maximum output length of [expand_message_xmd_len], `255 * b_in_bytes`.
+pub(crate) const XMD_MAX_LEN_IN_BYTES: usize = 255 * XMD_B_IN_BYTES;
+
+/// Reasons why expand_message_xmd cannot produce the requested output, e.g.,
+/// in [hash_to_scalar_fr](crate::hash_to_scalar_fr).
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ExpandMessageError {
+    #[error(
+        "Requested {0} bytes, but at most {} can be produced.",
+        XMD_MAX_LEN_IN_BYTES
+    )]
+    OutputTooLong(usize),
+    #[error("The domain separation string has {0} bytes, but at most 255 are allowed.")]
+    DstTooLong(usize),
 }
 
 /// Implements https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-5.4.1
-/// len_in_bytes is fixed to 128
-/// Domain separation string (dst) should be at most 255 bytes
-fn expand_message_xmd(msg: &[u8], dst: &[u8]) -> ([u8; 32], [u8; 32], [u8; 32], [u8; 32]) {
+/// with SHA-256 for any `len_in_bytes` up to the limit of the specification,
+/// [XMD_MAX_LEN_IN_BYTES]. Larger lengths, and domain separation strings of
+/// more than 255 bytes, are rejected before doing any work.
+pub(crate) fn expand_message_xmd_len(
+    msg: &[u8],
+    dst: &[u8],
+    len_in_bytes: usize,
+) -> Result<Vec<u8>, ExpandMessageError> {
+    if len_in_bytes > XMD_MAX_LEN_IN_BYTES {
+        return Err(ExpandMessageError::OutputTooLong(len_in_bytes));
+    }
+    let dst_len: u8 = dst
+        .len()
+        .try_into()
+        .map_err(|_| ExpandMessageError::DstTooLong(dst.len()))?;
+    // ell = ceil(len_in_bytes / b_in_bytes), at most 255 by the check above.
+    let ell = len_in_bytes.div_ceil(XMD_B_IN_BYTES) as u8;
     // DST_prime = DST || I2OSP(len(DST), 1)
     let mut dst_prime = dst.to_vec();
-    dst_prime.push(dst.len().try_into().unwrap()); // panics if dst is more than 255 bytes
-                                                   // msg_prime = Z_pad || msg || l_i_b_str || I2OSP(0, 1) || DST_prime
+    dst_prime.push(dst_len);
 
-    // b_0 = H(msg_prime)
+    // b_0 = H(msg_prime), msg_prime = Z_pad || msg || l_i_b_str || I2OSP(0, 1) ||
+    // DST_prime
     let mut h = Sha256::new();
-    // todo a possible optimization here would be to save the state of H(Z_pad)
-    h.update(vec![0; 64]); // z_pad = I2OSP(0, 64), 64 is the input block size of Sha265
+    h.update([0u8; 64]); // z_pad = I2OSP(0, 64), 64 is the input block size of Sha265
     h.update(msg);
-    h.update(vec![0, 128]); // l_i_b_str = I2OSP(128, 2)
+    h.update((len_in_bytes as u16).to_be_bytes()); // l_i_b_str = I2OSP(len_in_bytes, 2)
     h.update([0u8]);
     h.update(&dst_prime);
-    let mut b_0: [u8; 32] = [0u8; 32];
-    b_0.copy_from_slice(h.finalize().as_slice());
-
-    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
-    let mut h = Sha256::new();
-    h.update(b_0);
-    h.update([1u8]);
-    h.update(&dst_prime);
-    let mut b_1: [u8; 32] = [0u8; 32];
-    b_1.copy_from_slice(h.finalize().as_slice());
-
-    // b_2 = H(strxor(b_0, b_1)  || I2OSP(2, 1) || DST_prime)
-    let mut h = Sha256::new();
-    let xor: Vec<u8> = b_0.iter().zip(b_1.iter()).map(|(x, y)| x ^ y).collect();
-    h.update(xor);
-    h.update([2u8]);
-    h.update(&dst_prime);
-    let mut b_2: [u8; 32] = [0u8; 32];
-    b_2.copy_from_slice(h.finalize().as_slice());
-
-    // b_3 = H(strxor(b_1, b_2)  || I2OSP(3, 1) || DST_prime)
-    let mut h = Sha256::new();
-    let xor: Vec<u8> = b_0.iter().zip(b_2.iter()).map(|(x, y)| x ^ y).collect();
-    h.update(xor);
-    h.update([3u8]);
-    h.update(&dst_prime);
-    let mut b_3: [u8; 32] = [0u8; 32];
-    b_3.copy_from_slice(h.finalize().as_slice());
+    let b_0 = h.finalize();
+
+    let mut uniform_bytes = Vec::with_capacity(usize::from(ell) * XMD_B_IN_BYTES);
+    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime), and
+    // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime)
+    let mut b_i = [0u8; XMD_B_IN_BYTES];
+    for i in 1..=ell {
+        let mut h = Sha256::new();
+        let xor: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(x, y)| x ^ y).collect();
+        h.update(xor);
+        h.update([i]);
+        h.update(&dst_prime);
+        b_i.copy_from_slice(h.finalize().as_slice());
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    Ok(uniform_bytes)
+}
 
-    // b_4 = H(strxor(b_2, b_3)  || I2OSP(4, 1) || DST_prime)
-    let mut h = Sha256::new();
-    let xor: Vec<u8> = b_0.iter().zip(b_3.iter()).map(|(x, y)| x ^ y).collect();
-    h.update(xor);
-    h.update([4u8]);
-    h.update(dst_prime);
-    let mut b_4: [u8; 32] = [0u8; 32];
-    b_4.copy_from_slice(h.finalize().as_slice());
-
-    (b_1, b_2, b_3, b_4)
+/// Implements https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-5.4.1
+/// len_in_bytes is fixed to 128
+/// Domain separation string (dst) should be at most 255 bytes
+pub(crate) fn expand_message_xmd(
+    msg: &[u8],
+    dst: &[u8],
+) -> ([u8; 32], [u8; 32], [u8; 32], [u8; 32]) {
+    let bytes = expand_message_xmd_len(msg, dst, 128).expect("DST should be at most 255 bytes.");
+    let mut b = [[0u8; 32]; 4];
+    for (b_i, chunk) in b.iter_mut().zip(bytes.chunks(32)) {
+        b_i.copy_from_slice(chunk);
+    }
+    (b[0], b[1], b[2], b[3])
 }
 
 // Returns a point on E1 with coordinates x,y,z.
@@ -666,8 +677,8 @@ fn sswu_3mod4(u: Fq) -> (Fq, Fq, Fq, Fq) {
     let a = Fq::from_repr(FqRepr(E11_A)).unwrap(); // this unwrap can't fail, E11_A is an element of the field
     let b = Fq::from_repr(FqRepr(E11_B)).unwrap(); // this unwrap can't fail, E11_B is an element of the field
                                                    // Constants:
-                                                   // 1.  c1 = (q - 3) / 4           # Integer arithmetic
-                                                   // 2.  c2 = sqrt(-Z^3)
+                                                   // 1. c1 = (q - 3) / 4           # Integer arithmetic
+                                                   // 2. c2 = sqrt(-Z^3)
                                                    // Z = 11
     let z = Fq::from_repr(FqRepr::from(11)).unwrap();
     // c2 = sqrt(-z^3)
@@ -682,37 +693,37 @@ fn sswu_3mod4(u: Fq) -> (Fq, Fq, Fq, Fq) {
     .unwrap();
 
     // Steps:
-    // 1.  tv1 = u^2
+    // 1. tv1 = u^2
     let mut tv1 = u;
     tv1.square();
 
-    // 2.  tv3 = Z * tv1
+    // 2. tv3 = Z * tv1
     let mut tv3 = z;
     tv3.mul_assign(&tv1);
 
-    // 3.  tv2 = tv3^2
+    // 3. tv2 = tv3^2
     let mut tv2 = tv3;
     tv2.square();
 
-    // 4.   xd = tv2 + tv3
+    // 4. xd = tv2 + tv3
     let mut xd = tv2;
     xd.add_assign(&tv3);
 
-    // 5.  x1n = xd + 1
-    // 6.  x1n = x1n * B
+    // 5. x1n = xd + 1
+    // 6. x1n = x1n * B
     let mut x1n = xd;
     x1n.add_assign(&Fq::one());
     x1n.mul_assign(&b);
 
-    // 7.   xd = -A * xd
+    // 7. xd = -A * xd
     let mut neg_a = a;
     neg_a.negate();
     xd.mul_assign(&neg_a);
 
-    // 8.   e1 = xd == 0
+    // 8. e1 = xd == 0
     let e1 = xd.is_zero();
 
-    // 9.   xd = CMOV(xd, Z * A, e1)  # If xd == 0, set xd = Z * A
+    // 9. xd = CMOV(xd, Z * A, e1)  # If xd == 0, set xd = Z * A
     // We don't care if this is constant time or not.
     if e1 {
         xd = z;
@@ -758,25 +769,25 @@ fn sswu_3mod4(u: Fq) -> (Fq, Fq, Fq, Fq) {
     // 20. tv4 = tv4 * tv2            # gx1 * gxd^3
     tv4.mul_assign(&tv2);
 
-    // 21.  y1 = tv4^c1               # (gx1 * gxd^3)^((q - 3) / 4)
+    // 21. y1 = tv4^c1               # (gx1 * gxd^3)^((q - 3) / 4)
     let mut y1 = tv4;
     y1 = y1.pow(&P_MINUS_3_DIV_4);
 
-    // 22.  y1 = y1 * tv2             # gx1 * gxd * (gx1 * gxd^3)^((q - 3) / 4)
+    // 22. y1 = y1 * tv2             # gx1 * gxd * (gx1 * gxd^3)^((q - 3) / 4)
     y1.mul_assign(&tv2);
 
     // 23. x2n = tv3 * x1n            # x2 = x2n / xd = Z * u^2 * x1n / xd
     let mut x2n = tv3;
     x2n.mul_assign(&x1n);
 
-    // 24.  y2 = y1 * c2              # y2 = y1 * sqrt(-Z^3)
+    // 24. y2 = y1 * c2              # y2 = y1 * sqrt(-Z^3)
     let mut y2 = y1;
     y2.mul_assign(&c2);
 
-    // 25.  y2 = y2 * tv1
+    // 25. y2 = y2 * tv1
     y2.mul_assign(&tv1);
 
-    // 26.  y2 = y2 * u
+    // 26. y2 = y2 * u
     y2.mul_assign(&u);
 
     // 27. tv2 = y1^2
@@ -786,23 +797,23 @@ fn sswu_3mod4(u: Fq) -> (Fq, Fq, Fq, Fq) {
     // 28. tv2 = tv2 * gxd
     tv2.mul_assign(&gxd);
 
-    // 29.  e2 = tv2 == gx1
+    // 29. e2 = tv2 == gx1
     tv2.sub_assign(&gx1);
     let e2 = tv2.is_zero();
 
     let mut xn = x2n;
     let mut y = y2;
-    // 30.  xn = CMOV(x2n, x1n, e2)   # If e2, x = x1, else x = x2
-    // 31.   y = CMOV(y2, y1, e2)     # If e2, y = y1, else y = y2
+    // 30. xn = CMOV(x2n, x1n, e2)   # If e2, x = x1, else x = x2
+    // 31. y = CMOV(y2, y1, e2)     # If e2, y = y1, else y = y2
     if e2 {
         xn = x1n;
         y = y1;
     }
 
-    // 32.  e3 = sgn0(u) == sgn0(y)   # Fix sign of y
+    // 32. e3 = sgn0(u) == sgn0(y)   # Fix sign of y
     let e3 = sgn0(u) == sgn0(y);
 
-    // 33.   y = CMOV(-y, y, e3)
+    // 33. y = CMOV(-y, y, e3)
     if !e3 {
         y.negate();
     }
@@ -907,7 +918,42 @@ mod tests {
     use byteorder::{BigEndian, ReadBytesExt};
     use crypto_common::to_bytes;
     use ff::SqrtField;
-    use rand::{rngs::StdRng, thread_rng, SeedableRng};
+    use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+
+    // Check that fq_from_bytes agrees with reducing the 512-bit big-endian
+    // integer modulo the field characteristic using big integers.
+    #[test]
+    fn test_fq_from_bytes_reference() {
+        use ff::PrimeFieldRepr;
+        use num_bigint::BigUint;
+        let mut q_bytes = vec![];
+        Fq::char().write_be(&mut q_bytes).unwrap();
+        let q = BigUint::from_bytes_be(&q_bytes);
+        let check = |left: &[u8; 32], right: &[u8; 32]| {
+            let mut bytes = left.to_vec();
+            bytes.extend_from_slice(right);
+            let expected = BigUint::from_bytes_be(&bytes) % &q;
+            let mut actual = vec![];
+            fq_from_bytes(left, right)
+                .into_repr()
+                .write_be(&mut actual)
+                .unwrap();
+            assert_eq!(BigUint::from_bytes_be(&actual), expected);
+        };
+        // The inputs of hash_to_field in the test vectors.
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        for msg in [&b""[..], b"abc", b"abcdef0123456789"].iter() {
+            let (u_0, u_1, u_2, u_3) = expand_message_xmd(msg, dst);
+            check(&u_0, &u_1);
+            check(&u_2, &u_3);
+        }
+        check(&[0u8; 32], &[0u8; 32]);
+        check(&[0xffu8; 32], &[0xffu8; 32]);
+        let mut rng = thread_rng();
+        for _ in 0..1000 {
+            check(&rng.gen(), &rng.gen());
+        }
+    }
 
     // testing from_coordinates_unchecked for point at infinity
     #[test]
@@ -1205,6 +1251,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_expand_message_xmd_limits() {
+        let dst = b"QUUX-V01-CS02-with-expander";
+        let max = expand_message_xmd_len(b"abc", dst, XMD_MAX_LEN_IN_BYTES)
+            .expect("The maximum length should be allowed.");
+        assert_eq!(max.len(), XMD_MAX_LEN_IN_BYTES);
+        let short = expand_message_xmd_len(b"abc", dst, 33).expect("Short lengths are allowed.");
+        assert_eq!(short.len(), 33);
+        assert_eq!(
+            expand_message_xmd_len(b"abc", dst, XMD_MAX_LEN_IN_BYTES + 1),
+            Err(ExpandMessageError::OutputTooLong(XMD_MAX_LEN_IN_BYTES + 1))
+        );
+        assert_eq!(
+            expand_message_xmd_len(b"abc", dst, usize::MAX),
+            Err(ExpandMessageError::OutputTooLong(usize::MAX))
+        );
+        assert_eq!(
+            expand_message_xmd_len(b"abc", &[0u8; 256], 32),
+            Err(ExpandMessageError::DstTooLong(256))
+        );
+    }
+
     // For testing that a point is on the curve E: y^2 = x^3 + 4
     fn is_on_curve(x: Fq, y: Fq) -> bool {
         let mut y2 = y;
@@ -1,18 +1,16 @@
+use crate::bls12_381_g1hash::{expand_message_xmd_len, fq_from_bytes};
 use ff::{Field, PrimeField, SqrtField};
 use group::{CurveProjective, EncodedPoint};
+use lazy_static::lazy_static;
 use pairing::bls12_381::{Fq, Fq2, FqRepr, G2Uncompressed, G2};
-use sha2::{Digest, Sha256};
-use std::{
-    convert::TryInto,
-    io::{Cursor, Write},
-};
+use std::io::{Cursor, Write};
 
 /// Implements https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-3
 /// It follows the steps
 ///    1. u = hash_to_field(msg, 2)
 ///    2. Q0 = map_to_curve(u[0])
 ///    3. Q1 = map_to_curve(u[1])
-///    4. R = Q0 + Q1              
+///    4. R = Q0 + Q1
 ///    5. P = clear_cofactor(R) = h_eff * R   # Clearing cofactor
 ///    6. return P,
 /// where the choices of hash_to_field, map_to_curve and h_eff are as described in https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-8.8.2.
@@ -68,63 +66,86 @@ fn map_to_curve_g2(u: Fq2) -> G2 {
     from_coordinates_unchecked(x, y, z)
 }
 
+/// Constants of the simplified SWU map for the 3-isogenous curve E2'.
+#[derive(Debug, PartialEq)]
+struct SswuConstants {
+    a:  Fq2,
+    b:  Fq2,
+    z:  Fq2,
+    /// c1 = -B / A
+    c1: Fq2,
+    /// c2 = -1 / Z
+    c2: Fq2,
+}
+
+impl SswuConstants {
+    fn compute() -> Self {
+        let a = Fq2 {
+            c0: Fq::zero(),
+            c1: Fq::from_repr(FqRepr::from(240)).unwrap(),
+        };
+        let b = Fq2 {
+            c0: Fq::from_repr(FqRepr::from(1012)).unwrap(),
+            c1: Fq::from_repr(FqRepr::from(1012)).unwrap(),
+        };
+        let mut z = Fq2 {
+            c0: Fq::from_repr(FqRepr::from(2)).unwrap(),
+            c1: Fq::from_repr(FqRepr::from(1)).unwrap(),
+        };
+        z.negate();
+
+        // Constants:
+        // 1. c1 = -B / A
+        let mut c1 = a;
+        c1 = c1.inverse().expect("A is non-zero.");
+        c1.mul_assign(&b);
+        c1.negate();
+        // 2. c2 = -1 / Z
+        let mut c2 = z.inverse().expect("Z is non-zero.");
+        c2.negate();
+        SswuConstants { a, b, z, c1, c2 }
+    }
+}
+
+lazy_static! {
+    /// The constants are computed on first use, since computing c1 and c2
+    /// requires field inversions.
+    static ref SSWU_CONSTANTS: SswuConstants = SswuConstants::compute();
+}
+
 /// Implements https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-6.6.2
 /// This is not the optimized version described in https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#appendix-G.2.3
 #[allow(clippy::many_single_char_names)]
 fn sswu(u: Fq2) -> (Fq2, Fq2) {
-    let a = Fq2 {
-        c0: Fq::zero(),
-        c1: Fq::from_repr(FqRepr::from(240)).unwrap(),
-    };
-    let b = Fq2 {
-        c0: Fq::from_repr(FqRepr::from(1012)).unwrap(),
-        c1: Fq::from_repr(FqRepr::from(1012)).unwrap(),
-    };
-    let mut z = Fq2 {
-        c0: Fq::from_repr(FqRepr::from(2)).unwrap(),
-        c1: Fq::from_repr(FqRepr::from(1)).unwrap(),
-    };
-    z.negate();
-
-    // Constants:
-    // 1.  c1 = -B / A
-    let mut c1 = a;
-    c1 = c1.inverse().unwrap();
-    c1.mul_assign(&b);
-    c1.negate();
-    // 2.  c2 = -1 / Z
-    let mut c2 = z.inverse().unwrap();
-    c2.negate();
-
-    // all values above are constants
+    let SswuConstants { a, b, z, c1, c2 } = *SSWU_CONSTANTS;
 
     // Steps:
-    // 1.  tv1 = Z * u^2
+    // 1. tv1 = Z * u^2
     let mut tv1 = u;
     tv1.square();
     tv1.mul_assign(&z);
-    // 2.  tv2 = tv1^2
+    // 2. tv2 = tv1^2
     let mut tv2 = tv1;
     tv2.square();
-    // 3.   x1 = tv1 + tv2
+    // 3. x1 = tv1 + tv2
     let mut x1 = tv1;
     x1.add_assign(&tv2);
-    // 4.   x1 = inv0(x1)
+    // 4. x1 = inv0(x1)
     x1 = match x1.inverse() {
         None => Fq2::zero(),
         Some(x1inv) => x1inv,
     };
-    // 5.   e1 = x1 == 0
+    // 5. e1 = x1 == 0
     let e1 = x1.is_zero();
-    // 6.   x1 = x1 + 1
+    // 6. x1 = x1 + 1
     x1.add_assign(&Fq2::one());
-    // 7.   x1 = CMOV(x1, c2, e1)    # If (tv1 + tv2) == 0, set x1 = -1 / Z
+    // 7. x1 = CMOV(x1, c2, e1)    # If (tv1 + tv2) == 0, set x1 = -1 / Z
     if e1 {
         x1 = c2;
     }
-    // 8.   x1 = x1 * c1      # x1 = (-B / A) * (1 + (1 / (Z^2 * u^4 + Z * u^2)))
+    // 8. x1 = x1 * c1      # x1 = (-B / A) * (1 + (1 / (Z^2 * u^4 + Z * u^2)))
     x1.mul_assign(&c1);
-    // 9.  gx1 = x1^2
+    // 9. gx1 = x1^2
     let mut gx1 = x1;
     gx1.square();
     // 10. gx1 = gx1 + A
@@ -133,7 +154,7 @@ fn sswu(u: Fq2) -> (Fq2, Fq2) {
     gx1.mul_assign(&x1);
     // 12. gx1 = gx1 + B             # gx1 = g(x1) = x1^3 + A * x1 + B
     gx1.add_assign(&b);
-    // 13.  x2 = tv1 * x1            # x2 = Z * u^2 * x1
+    // 13. x2 = tv1 * x1            # x2 = Z * u^2 * x1
     let mut x2 = tv1;
     x2.mul_assign(&x1);
     // 14. tv2 = tv1 * tv2
@@ -141,21 +162,23 @@ fn sswu(u: Fq2) -> (Fq2, Fq2) {
     // 15. gx2 = gx1 * tv2           # gx2 = (Z * u^2)^3 * gx1
     let mut gx2 = gx1;
     gx2.mul_assign(&tv2);
-    // 16.  e2 = is_square(gx1)
-    let e2 = gx1.sqrt().is_some();
-    // 17.   x = CMOV(x2, x1, e2)    # If is_square(gx1), x = x1, else x = x2
-    // 18.  y2 = CMOV(gx2, gx1, e2)  # If is_square(gx1), y2 = gx1, else y2 = gx2
-    let mut x = x2;
-    let mut y2 = gx2;
-    if e2 {
-        x = x1;
-        y2 = gx1;
-    }
-    // 19.   y = sqrt(y2)
-    let mut y = y2.sqrt().unwrap();
-    // 20.  e3 = sgn0(u) == sgn0(y)  # Fix sign of y
+    // 16. e2 = is_square(gx1)
+    // 17. x = CMOV(x2, x1, e2)    # If is_square(gx1), x = x1, else x = x2
+    // 18. y2 = CMOV(gx2, gx1, e2)  # If is_square(gx1), y2 = gx1, else y2 = gx2
+    // 19. y = sqrt(y2)
+    // If gx1 is not a square, then gx2 = (Z * u^2)^3 * gx1 is, since Z is not a
+    // square. A failure here means that one of the constants is wrong.
+    let (x, mut y) = match gx1.sqrt() {
+        Some(y) => (x1, y),
+        None => (
+            x2,
+            gx2.sqrt()
+                .expect("SSWU: g(x2) must be a square when g(x1) is not. Check the constants."),
+        ),
+    };
+    // 20. e3 = sgn0(u) == sgn0(y)  # Fix sign of y
     let e3 = sgn0(u) == sgn0(y);
-    // 21.   y = CMOV(-y, y, e3)
+    // 21. y = CMOV(-y, y, e3)
     if !e3 {
         y.negate();
     }
@@ -175,45 +198,11 @@ fn sgn0(x: Fq2) -> u64 {
 /// len_in_bytes is fixed to 256
 /// Domain separation string (dst) should be at most 255 bytes
 fn expand_message_xmd(msg: &[u8], dst: &[u8]) -> [[u8; 32]; 8] {
-    // DST_prime = DST || I2OSP(len(DST), 1)
-    let mut dst_prime = dst.to_vec();
-    dst_prime.push(dst.len().try_into().unwrap()); // panics if dst is more than 255 bytes
-
-    // b_0 = H(msg_prime), msg_prime = Z_pad || msg || l_i_b_str || I2OSP(0, 1) ||
-    // DST_prime
-    let mut h = Sha256::new();
-    h.update(vec![0; 64]); // z_pad = I2OSP(0, 64), 64 is the input block size of Sha265
-    h.update(msg);
-    h.update(vec![1, 0]); // l_i_b_str = I2OSP(256, 2)
-    h.update([0u8]);
-    h.update(&dst_prime);
-    let mut b_0: [u8; 32] = [0u8; 32];
-    b_0.copy_from_slice(h.finalize().as_slice());
-
-    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
-    let mut h = Sha256::new();
-    h.update(b_0);
-    h.update([1u8]);
-    h.update(&dst_prime);
-
-    let mut b = [[0u8; 32]; 8]; // b[i] corresponds to b_i+1 in specification.
-    b[0].copy_from_slice(h.finalize().as_slice());
-
-    // compute remaining uniform bytes
-    for i in 1u8..8 {
-        // b_i = H(strxor(b_0, b_i-1)  || I2OSP(i, 1) || DST_prime)
-        let mut h = Sha256::new();
-        let xor: Vec<u8> = b_0
-            .iter()
-            .zip(b[i as usize - 1].iter())
-            .map(|(x, y)| x ^ y)
-            .collect();
-        h.update(xor);
-        h.update([i + 1]); // offset as standard drops b_0 and returns index b_1-b_8
-        h.update(&dst_prime);
-        b[i as usize].copy_from_slice(h.finalize().as_slice());
+    let bytes = expand_message_xmd_len(msg, dst, 256).expect("DST should be at most 255 bytes.");
+    let mut b = [[0u8; 32]; 8];
+    for (b_i, chunk) in b.iter_mut().zip(bytes.chunks(32)) {
+        b_i.copy_from_slice(chunk);
     }
-
     b
 }
 
@@ -233,30 +222,6 @@ fn hash_to_field_fq2(msg: &[u8], dst: &[u8]) -> (Fq2, Fq2) {
     (u0, u1)
 }
 
-// Interpret input as integers (big endian)
-// Return (left*2^256 + right) as Fq
-fn fq_from_bytes(left_bytes: &[u8; 32], right_bytes: &[u8; 32]) -> Fq {
-    fn le_u64s_from_be_bytes(bytes: &[u8; 32]) -> Fq {
-        let mut digits = [0u64; 6];
-
-        for (place, chunk) in digits.iter_mut().zip(bytes.chunks(8).rev()) {
-            *place = u64::from_be_bytes(chunk.try_into().expect("Chunk Sie is always 8"))
-        }
-
-        Fq::from_repr(FqRepr(digits)).expect("Only the leading 4 u64s are initialized")
-    }
-
-    let two_to_256_fqrepr = [0u64, 0, 0, 0, 1, 0]; // 2^256
-    let two_to_256_fq = Fq::from_repr(FqRepr(two_to_256_fqrepr)).expect("2^256 fits in modulus");
-
-    let mut left_fq = le_u64s_from_be_bytes(left_bytes);
-    let right_fq = le_u64s_from_be_bytes(right_bytes);
-    left_fq.mul_assign(&two_to_256_fq); // u_0[..32] * 2^256
-    left_fq.add_assign(&right_fq); // u_0[32..] + u_0[32..] * 2^256 = u_0
-
-    left_fq
-}
-
 /// Computes the 3-isogeny map for G2, specified in
 /// https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#appendix-E.3
 fn iso_map(x: Fq2, y: Fq2, z: Fq2) -> (Fq2, Fq2, Fq2) {
@@ -569,6 +534,88 @@ fn from_coordinates_unchecked(x: Fq2, y: Fq2, z: Fq2) -> G2 {
 mod tests {
     use super::*;
 
+    /// Check that the point `(x, y, z)` in Jacobian coordinates, as output by
+    /// [iso_map], is on the curve E: y^2 = x^3 + 4(u + 1), and not merely on
+    /// the isogenous curve E'. In Jacobian coordinates the equation is
+    /// y^2 = x^3 + 4(u + 1) z^6.
+    fn iso_map_check((x, y, z): (Fq2, Fq2, Fq2)) -> bool {
+        let mut lhs = y;
+        lhs.square();
+        let mut rhs = x;
+        rhs.square();
+        rhs.mul_assign(&x);
+        let mut z6 = z;
+        z6.square();
+        let z2 = z6;
+        z6.square();
+        z6.mul_assign(&z2);
+        let b = Fq2 {
+            c0: Fq::from_str("4").unwrap(),
+            c1: Fq::from_str("4").unwrap(),
+        };
+        z6.mul_assign(&b);
+        rhs.add_assign(&z6);
+        lhs == rhs
+    }
+
+    /// Check that the output `(x, y)` of [sswu] is on the curve E':
+    /// y^2 = x^3 + A x + B, where A = 240 u and B = 1012(1 + u), with the sign
+    /// of y matching that of the input.
+    fn sswu_check(u: Fq2) {
+        let (x, y) = sswu(u);
+        let a = Fq2 {
+            c0: Fq::zero(),
+            c1: Fq::from_str("240").unwrap(),
+        };
+        let b = Fq2 {
+            c0: Fq::from_str("1012").unwrap(),
+            c1: Fq::from_str("1012").unwrap(),
+        };
+        let mut lhs = y;
+        lhs.square();
+        let mut rhs = x;
+        rhs.square();
+        rhs.add_assign(&a);
+        rhs.mul_assign(&x);
+        rhs.add_assign(&b);
+        assert_eq!(lhs, rhs, "(x, y) should be on E' for u = {}.", u);
+        assert_eq!(sgn0(u), sgn0(y), "The sign of y should match u.");
+    }
+
+    #[test]
+    fn test_sswu_constants() {
+        let cached = &*SSWU_CONSTANTS;
+        assert_eq!(cached, &SswuConstants::compute());
+        // c1 * A = -B
+        let mut lhs = cached.c1;
+        lhs.mul_assign(&cached.a);
+        let mut neg_b = cached.b;
+        neg_b.negate();
+        assert_eq!(lhs, neg_b, "c1 should be -B / A.");
+        // c2 * Z = -1
+        let mut lhs = cached.c2;
+        lhs.mul_assign(&cached.z);
+        let mut neg_one = Fq2::one();
+        neg_one.negate();
+        assert_eq!(lhs, neg_one, "c2 should be -1 / Z.");
+    }
+
+    #[test]
+    fn test_sswu_square_branches() {
+        let dst = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        for msg in [&b""[..], b"abc", b"abcdef0123456789"].iter() {
+            let (u0, u1) = hash_to_field_fq2(msg, dst);
+            sswu_check(u0);
+            sswu_check(u1);
+        }
+        // Random inputs exercise both branches, g(x1) square or not.
+        let mut rng = rand::thread_rng();
+        for _ in 0..64 {
+            sswu_check(Fq2::random(&mut rng));
+        }
+        sswu_check(Fq2::zero());
+    }
+
     #[test]
     fn test_hash_to_field_fq2() {
         // https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#appendix-J.10.1
@@ -579,11 +626,11 @@ mod tests {
             //    u[0]    = 03dbc2cce174e91ba93cbb08f26b917f98194a2ea08d1cce75b2b9
             //              cc9f21689d80bd79b594a613d0a68eb807dfdc1cf8
             //        + I * 05a2acec64114845711a54199ea339abd125ba38253b70a92c876d
-            //              f10598bd1986b739cad67961eb94f7076511b3b39a
+            //          f10598bd1986b739cad67961eb94f7076511b3b39a
             //    u[1]    = 02f99798e8a5acdeed60d7e18e9120521ba1f47ec090984662846b
             //              c825de191b5b7641148c0dbc237726a334473eee94
             //        + I * 145a81e418d4010cc027a68f14391b30074e89e60ee7a22f87217b
-            //              2f6eb0c4b94c9115b436e6fa4607e95a98de30a435
+            //          2f6eb0c4b94c9115b436e6fa4607e95a98de30a435
             let msg = b"";
             let (u0, u1) = hash_to_field_fq2(msg, dst);
             assert_eq!(
@@ -605,11 +652,11 @@ mod tests {
             // u[0]    = 15f7c0aa8f6b296ab5ff9c2c7581ade64f4ee6f1bf18f55179ff44
             //         a2cf355fa53dd2a2158c5ecb17d7c52f63e7195771
             //   + I * 01c8067bf4c0ba709aa8b9abc3d1cef589a4758e09ef53732d670f
-            //         d8739a7274e111ba2fcaa71b3d33df2a3a0c8529dd
+            //     d8739a7274e111ba2fcaa71b3d33df2a3a0c8529dd
             // u[1]    = 187111d5e088b6b9acfdfad078c4dacf72dcd17ca17c82be35e79f
             //         8c372a693f60a033b461d81b025864a0ad051a06e4
             //   + I * 08b852331c96ed983e497ebc6dee9b75e373d923b729194af8e72a
-            //         051ea586f3538a6ebb1e80881a082fa2b24df9f566
+            //     051ea586f3538a6ebb1e80881a082fa2b24df9f566
             let msg = b"abc";
             let (u0, u1) = hash_to_field_fq2(msg, dst);
             assert_eq!(
@@ -631,6 +678,18 @@ mod tests {
     fn test_hash_to_curve_g2() {
         // Test vectors are from https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#appendix-J.10.1
         let dst = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        for msg in [&b""[..], b"abc", b"abcdef0123456789"].iter() {
+            let (u0, u1) = hash_to_field_fq2(msg, dst);
+            for u in [u0, u1].iter() {
+                let (x, y) = sswu(*u);
+                assert!(
+                    iso_map_check(iso_map(x, y, Fq2::one())),
+                    "The isogeny should map to E."
+                );
+                // Check that the check can fail, using the (x, y) on E'.
+                assert!(!iso_map_check((x, y, Fq2::one())), "(x, y) is on E'.");
+            }
+        }
         {
             //    msg     =
             //    P.x     =
@@ -6,16 +6,34 @@ use ff::{Field, PrimeField};
 use group::{CurveAffine, CurveProjective, EncodedPoint};
 use pairing::{
     bls12_381::{
-        Bls12, Fq, Fr, FrRepr, G1Affine, G1Compressed, G1Prepared, G2Affine, G2Compressed,
+        Bls12, Fq, FqRepr, Fr, FrRepr, G1Affine, G1Compressed, G1Prepared, G2Affine, G2Compressed,
         G2Prepared, G1, G2,
     },
     Engine, PairingCurveAffine,
 };
 use rand::*;
+use std::convert::TryInto;
 
 const HASH_TO_GROUP_G1_DST: &[u8; 55] = b"CONCORDIUM-hashtoG1-with-BLS12381G1_XMD:SHA-256_SSWU_RO";
 const HASH_TO_GROUP_G2_DST: &[u8; 55] = b"CONCORDIUM-hashtoG2-with-BLS12381G2_XMD:SHA-256_SSWU_RO";
 
+/// Domain separation string used by [Curve::hash_to_scalar] for the BLS12-381
+/// instances.
+pub const HASH_TO_SCALAR_DST: &[u8; 45] = b"CONCORDIUM-hashtoFr-with-BLS12381_XMD:SHA-256";
+
+/// The order r of the BLS12-381 groups in big-endian.
+const SCALAR_MODULUS_BYTES: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// The characteristic q of the BLS12-381 base field in big-endian.
+const BASE_MODULUS_BYTES: [u8; 48] = [
+    0x1a, 0x01, 0x11, 0xea, 0x39, 0x7f, 0xe6, 0x9a, 0x4b, 0x1b, 0xa7, 0xb6, 0x43, 0x4b, 0xac, 0xd7,
+    0x64, 0x77, 0x4b, 0x84, 0xf3, 0x85, 0x12, 0xbf, 0x67, 0x30, 0xd2, 0xa0, 0xf6, 0xb0, 0xf6, 0x24,
+    0x1e, 0xab, 0xff, 0xfe, 0xb1, 0x53, 0xff, 0xff, 0xb9, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xaa, 0xab,
+];
+
 // Helper function for both G1 and G2 instances.
 fn scalar_from_bytes_helper<A: AsRef<[u8]>>(bytes: A) -> Fr {
     // Traverse at most 4 8-byte chunks, for a total of 256 bits.
@@ -31,6 +49,133 @@ fn scalar_from_bytes_helper<A: AsRef<[u8]>>(bytes: A) -> Fr {
     Fr::from_repr(FrRepr(fr)).expect("The scalar with top two bits erased should be valid.")
 }
 
+// Interpret the input as a big endian integer and reduce it modulo the order
+// of Fr. The input is processed in 16 byte chunks, each of which is less than
+// the modulus, so that only field operations are used.
+fn fr_from_wide_be_bytes(bytes: &[u8; 64]) -> Fr {
+    let two_to_128 = Fr::from_repr(FrRepr([0, 0, 1, 0])).expect("2^128 fits in modulus");
+    let mut acc = Fr::zero();
+    for chunk in bytes.chunks(16) {
+        let hi = u64::from_be_bytes(chunk[..8].try_into().expect("Chunk size is 16."));
+        let lo = u64::from_be_bytes(chunk[8..].try_into().expect("Chunk size is 16."));
+        let x = Fr::from_repr(FrRepr([lo, hi, 0, 0])).expect("128-bit values fit in modulus");
+        acc.mul_assign(&two_to_128);
+        acc.add_assign(&x);
+    }
+    acc
+}
+
+// Helper function for both G1 and G2 instances. The input is processed in 32
+// byte chunks, each of which is less than the modulus, so that only field
+// operations are used. The first chunk is the possibly shorter remainder.
+fn fq_from_be_bytes(bytes: &[u8]) -> Result<Fq, FieldDecodingError> {
+    if bytes.is_empty() {
+        return Err(FieldDecodingError::EmptyInput);
+    }
+    let two_to_256 = Fq::from_repr(FqRepr([0, 0, 0, 0, 1, 0])).expect("2^256 fits in modulus");
+    let first = match bytes.len() % 32 {
+        0 => 32,
+        r => r,
+    };
+    let (head, tail) = bytes.split_at(first);
+    let mut acc = Fq::zero();
+    for chunk in std::iter::once(head).chain(tail.chunks(32)) {
+        let mut digits = [0u64; 6];
+        let mut padded = [0u8; 32];
+        padded[32 - chunk.len()..].copy_from_slice(chunk);
+        for (place, limb) in digits.iter_mut().zip(padded.chunks(8).rev()) {
+            *place = u64::from_be_bytes(limb.try_into().expect("Chunk size is 8."));
+        }
+        let x = Fq::from_repr(FqRepr(digits)).expect("256-bit values fit in modulus");
+        acc.mul_assign(&two_to_256);
+        acc.add_assign(&x);
+    }
+    Ok(acc)
+}
+
+/// Hash the message to the scalar field of BLS12-381 with the given domain
+/// separation string. This fails if the DST is longer than 255 bytes; see
+/// [DomainSeparation::build](crate::DomainSeparation::build) for shortening
+/// long DSTs. This follows
+/// hash_to_field from
+/// <https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-5.3>
+/// with expand_message_xmd, except that 64 bytes of output are reduced
+/// instead of 48, which makes the bias of the result negligible.
+///
+/// With [HASH_TO_SCALAR_DST] this is [Curve::hash_to_scalar] of the BLS12-381
+/// instances.
+pub fn hash_to_scalar_fr(msg: &[u8], dst: &[u8]) -> Result<Fr, ExpandMessageError> {
+    // The expansion is 128 bytes long, of which the first 64 are used, to keep
+    // the output the same as when this used the fixed 128 byte expansion.
+    let bytes = expand_message_xmd_len(msg, dst, 128)?;
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&bytes[..64]);
+    Ok(fr_from_wide_be_bytes(&wide))
+}
+
+// Helper function for both G1 and G2 instances.
+fn hash_to_scalar_helper(bytes: &[u8]) -> Fr {
+    hash_to_scalar_fr(bytes, HASH_TO_SCALAR_DST).expect("HASH_TO_SCALAR_DST is a valid DST.")
+}
+
+const DERIVE_GENERATORS_G1_DST: &[u8; 36] = b"CONCORDIUM-derive-generators-G1-idx-";
+const DERIVE_GENERATORS_G2_DST: &[u8; 36] = b"CONCORDIUM-derive-generators-G2-idx-";
+
+// Helper function for both G1 and G2. The i-th attempt hashes the seed with the
+// domain separation string suffixed by i in big endian. Attempts that yield the
+// zero point or a point that was already derived are skipped, which keeps the
+// result reproducible from the seed.
+fn derive_generators_helper<C: Curve>(
+    seed: &[u8],
+    n: usize,
+    dst: &[u8],
+    hash: impl Fn(&[u8], &[u8]) -> C,
+) -> Vec<C> {
+    let mut out: Vec<C> = Vec::with_capacity(n);
+    let mut dst_i = dst.to_vec();
+    let mut i: u64 = 0;
+    while out.len() < n {
+        dst_i.truncate(dst.len());
+        dst_i.extend_from_slice(&i.to_be_bytes());
+        let g = hash(seed, &dst_i);
+        if !g.is_zero_point() && !out.contains(&g) {
+            out.push(g);
+        }
+        i += 1;
+    }
+    out
+}
+
+/// `sgn0` of the y-coordinate of an uncompressed G1 point, which is the parity
+/// of the big-endian integer in the last 48 bytes.
+fn sgn0_y_g1(p: &G1Affine) -> bool {
+    let bytes = p.into_uncompressed();
+    bytes.as_ref()[95] & 1 == 1
+}
+
+/// `sgn0` of the y-coordinate of an uncompressed G2 point. The y-coordinate is
+/// `c0 + c1 * u`, encoded as `c1` followed by `c0`, and its sign is that of
+/// `c0`, unless `c0` is zero, in which case it is that of `c1`.
+fn sgn0_y_g2(p: &G2Affine) -> bool {
+    let bytes = p.into_uncompressed();
+    let (c1, c0) = bytes.as_ref()[96..].split_at(48);
+    let c0_zero = c0.iter().all(|b| *b == 0);
+    c0[47] & 1 == 1 || (c0_zero && c1[47] & 1 == 1)
+}
+
+/// Derive `n` distinct, non-zero generators of G1 from the seed by hashing it
+/// to the curve with an index-suffixed domain separation string. The same seed
+/// always gives the same generators, and nobody knows the discrete logarithms
+/// of the generators with respect to each other.
+pub fn derive_generators_g1(seed: &[u8], n: usize) -> Vec<G1> {
+    derive_generators_helper(seed, n, DERIVE_GENERATORS_G1_DST, hash_to_curve)
+}
+
+/// Analogous to [derive_generators_g1], but for G2.
+pub fn derive_generators_g2(seed: &[u8], n: usize) -> Vec<G2> {
+    derive_generators_helper(seed, n, DERIVE_GENERATORS_G2_DST, hash_to_curve_g2)
+}
+
 impl Curve for G2 {
     type Base = Fq;
     type Compressed = G2Compressed;
@@ -51,6 +196,8 @@ impl Curve for G2 {
 
     fn is_zero_point(&self) -> bool { self.is_zero() }
 
+    fn is_canonical_sign(&self) -> bool { !sgn0_y_g2(&self.into_affine()) }
+
     fn double_point(&self) -> Self {
         let mut x = *self;
         x.double();
@@ -102,6 +249,10 @@ impl Curve for G2 {
         scalar_from_bytes_helper(bytes)
     }
 
+    fn base_from_be_bytes(bytes: &[u8]) -> Result<Self::Base, FieldDecodingError> {
+        fq_from_be_bytes(bytes)
+    }
+
     fn bytes_to_curve_unchecked<R: ReadBytesExt>(bytes: &mut R) -> anyhow::Result<Self> {
         let mut g = G2Compressed::empty();
         bytes.read_exact(g.as_mut())?;
@@ -113,6 +264,12 @@ impl Curve for G2 {
     fn generate_scalar<T: Rng>(csprng: &mut T) -> Self::Scalar { Fr::random(csprng) }
 
     fn hash_to_group(b: &[u8]) -> Self { hash_to_curve_g2(b, HASH_TO_GROUP_G2_DST) }
+
+    fn hash_to_scalar(b: &[u8]) -> Self::Scalar { hash_to_scalar_helper(b) }
+
+    fn scalar_modulus_bytes() -> &'static [u8] { &SCALAR_MODULUS_BYTES }
+
+    fn base_modulus_bytes() -> &'static [u8] { &BASE_MODULUS_BYTES }
 }
 
 impl Curve for G1 {
@@ -135,6 +292,8 @@ impl Curve for G1 {
 
     fn is_zero_point(&self) -> bool { self.is_zero() }
 
+    fn is_canonical_sign(&self) -> bool { !sgn0_y_g1(&self.into_affine()) }
+
     fn double_point(&self) -> Self {
         let mut x = *self;
         x.double();
@@ -186,6 +345,10 @@ impl Curve for G1 {
         scalar_from_bytes_helper(bytes)
     }
 
+    fn base_from_be_bytes(bytes: &[u8]) -> Result<Self::Base, FieldDecodingError> {
+        fq_from_be_bytes(bytes)
+    }
+
     fn bytes_to_curve_unchecked<R: ReadBytesExt>(bytes: &mut R) -> anyhow::Result<Self> {
         let mut g = G1Compressed::empty();
         bytes.read_exact(g.as_mut())?;
@@ -197,6 +360,12 @@ impl Curve for G1 {
     fn generate_scalar<T: Rng>(csprng: &mut T) -> Self::Scalar { Fr::random(csprng) }
 
     fn hash_to_group(bytes: &[u8]) -> Self { hash_to_curve(bytes, HASH_TO_GROUP_G1_DST) }
+
+    fn hash_to_scalar(b: &[u8]) -> Self::Scalar { hash_to_scalar_helper(b) }
+
+    fn scalar_modulus_bytes() -> &'static [u8] { &SCALAR_MODULUS_BYTES }
+
+    fn base_modulus_bytes() -> &'static [u8] { &BASE_MODULUS_BYTES }
 }
 
 impl Curve for G1Affine {
@@ -219,6 +388,8 @@ impl Curve for G1Affine {
 
     fn is_zero_point(&self) -> bool { self.is_zero() }
 
+    fn is_canonical_sign(&self) -> bool { !sgn0_y_g1(self) }
+
     fn double_point(&self) -> Self {
         let mut x = self.into_projective();
         x.double();
@@ -267,6 +438,10 @@ impl Curve for G1Affine {
         scalar_from_bytes_helper(bytes)
     }
 
+    fn base_from_be_bytes(bytes: &[u8]) -> Result<Self::Base, FieldDecodingError> {
+        fq_from_be_bytes(bytes)
+    }
+
     fn bytes_to_curve_unchecked<R: ReadBytesExt>(bytes: &mut R) -> anyhow::Result<Self> {
         let mut g = G1Compressed::empty();
         bytes.read_exact(g.as_mut())?;
@@ -278,6 +453,12 @@ impl Curve for G1Affine {
     fn generate_scalar<T: Rng>(csprng: &mut T) -> Self::Scalar { Fr::random(csprng) }
 
     fn hash_to_group(b: &[u8]) -> Self { hash_to_curve(b, HASH_TO_GROUP_G1_DST).into_affine() }
+
+    fn hash_to_scalar(b: &[u8]) -> Self::Scalar { hash_to_scalar_helper(b) }
+
+    fn scalar_modulus_bytes() -> &'static [u8] { &SCALAR_MODULUS_BYTES }
+
+    fn base_modulus_bytes() -> &'static [u8] { &BASE_MODULUS_BYTES }
 }
 
 impl Curve for G2Affine {
@@ -300,6 +481,8 @@ impl Curve for G2Affine {
 
     fn is_zero_point(&self) -> bool { self.is_zero() }
 
+    fn is_canonical_sign(&self) -> bool { !sgn0_y_g2(self) }
+
     fn double_point(&self) -> Self {
         let mut x = self.into_projective();
         x.double();
@@ -348,6 +531,10 @@ impl Curve for G2Affine {
         scalar_from_bytes_helper(bytes)
     }
 
+    fn base_from_be_bytes(bytes: &[u8]) -> Result<Self::Base, FieldDecodingError> {
+        fq_from_be_bytes(bytes)
+    }
+
     fn bytes_to_curve_unchecked<R: ReadBytesExt>(bytes: &mut R) -> anyhow::Result<Self> {
         let mut g = G2Compressed::empty();
         bytes.read_exact(g.as_mut())?;
@@ -359,6 +546,12 @@ impl Curve for G2Affine {
     fn generate_scalar<T: Rng>(csprng: &mut T) -> Self::Scalar { Fr::random(csprng) }
 
     fn hash_to_group(b: &[u8]) -> Self { hash_to_curve_g2(b, HASH_TO_GROUP_G2_DST).into_affine() }
+
+    fn hash_to_scalar(b: &[u8]) -> Self::Scalar { hash_to_scalar_helper(b) }
+
+    fn scalar_modulus_bytes() -> &'static [u8] { &SCALAR_MODULUS_BYTES }
+
+    fn base_modulus_bytes() -> &'static [u8] { &BASE_MODULUS_BYTES }
 }
 
 impl Pairing for Bls12 {
@@ -398,6 +591,121 @@ mod tests {
     use crypto_common::*;
     use std::io::Cursor;
 
+    /// An RNG that returns zero for the first `zeros` words, and after that
+    /// the number of words requested so far.
+    struct ZeroThenCount {
+        zeros: u64,
+        calls: u64,
+    }
+
+    impl rand::RngCore for ZeroThenCount {
+        fn next_u32(&mut self) -> u32 { self.next_u64() as u32 }
+
+        fn next_u64(&mut self) -> u64 {
+            self.calls += 1;
+            if self.calls <= self.zeros {
+                0
+            } else {
+                self.calls
+            }
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let word = self.next_u64().to_le_bytes();
+                chunk.copy_from_slice(&word[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    // Check that sampling a non-zero scalar retries when the first sample is
+    // zero.
+    #[test]
+    fn generate_non_zero_scalar_retries() {
+        // Sanity check: the first scalar drawn from the RNG is zero.
+        let mut rng = ZeroThenCount { zeros: 4, calls: 0 };
+        assert!(G1::generate_scalar(&mut rng).is_zero());
+        let mut rng = ZeroThenCount { zeros: 4, calls: 0 };
+        let s = G1::generate_non_zero_scalar(&mut rng);
+        assert!(!s.is_zero());
+        assert!(rng.calls > 4, "A second scalar should have been drawn.");
+    }
+
+    #[test]
+    fn hash_input_to_scalar() {
+        let separate = hash_input::HashInput::new().with(b"ab").with(b"cd");
+        let joined = hash_input::HashInput::new().with(b"abcd");
+        assert_eq!(
+            separate.finalize_to_scalar::<G1>(),
+            G1::hash_to_scalar(separate.as_bytes())
+        );
+        assert_ne!(
+            separate.finalize_to_scalar::<G1>(),
+            joined.finalize_to_scalar::<G1>()
+        );
+    }
+
+    // Check a BLS-style verification equation e(sig, g2) * e(-H(m), pk) = 1.
+    #[test]
+    fn pairing_check_bls_equation() {
+        let mut rng = rand::thread_rng();
+        let sk = <Bls12 as Pairing>::generate_non_zero_scalar(&mut rng);
+        let pk = G2::one_point().mul_by_scalar(&sk);
+        let hash = G1::hash_to_group(b"message");
+        let sig = hash.mul_by_scalar(&sk);
+        let terms = [(sig, G2::one_point()), (hash.inverse_point(), pk)];
+        assert!(<Bls12 as Pairing>::pairing_check(&terms), "Valid equation.");
+        assert!(<Bls12 as Pairing>::pairing_check(&[]), "Empty product.");
+        let tampered = [
+            (sig.plus_point(&G1::one_point()), G2::one_point()),
+            (hash.inverse_point(), pk),
+        ];
+        assert!(
+            !<Bls12 as Pairing>::pairing_check(&tampered),
+            "Tampered signature."
+        );
+        let wrong_message = [
+            (sig, G2::one_point()),
+            (G1::hash_to_group(b"other").inverse_point(), pk),
+        ];
+        assert!(
+            !<Bls12 as Pairing>::pairing_check(&wrong_message),
+            "Wrong message."
+        );
+    }
+
+    // Check that pairs of points are serialized as the concatenation of the
+    // point encodings.
+    #[test]
+    fn point_pairs_serialization() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let (a, b, c) = (
+                G1::generate(&mut rng),
+                G1::generate(&mut rng),
+                G2::generate(&mut rng),
+            );
+            let same = to_bytes(&(a, b));
+            assert_eq!(same.len(), 2 * G1::GROUP_ELEMENT_LENGTH);
+            assert_eq!(same, [to_bytes(&a), to_bytes(&b)].concat());
+            let mixed = to_bytes(&(a, c));
+            assert_eq!(
+                mixed.len(),
+                G1::GROUP_ELEMENT_LENGTH + G2::GROUP_ELEMENT_LENGTH
+            );
+            assert_eq!(mixed, [to_bytes(&a), to_bytes(&c)].concat());
+            let same_back: (G1, G1) = from_bytes(&mut Cursor::new(&same)).expect("Valid pair.");
+            assert_eq!(same_back, (a, b));
+            let mixed_back: (G1, G2) = from_bytes(&mut Cursor::new(&mixed)).expect("Valid pair.");
+            assert_eq!(mixed_back, (a, c));
+        }
+    }
+
     // Check that scalar_from_bytes_helper works on small values.
     #[test]
     fn scalar_from_bytes_small() {
@@ -418,6 +726,255 @@ mod tests {
         }
     }
 
+    // Check that reduction of wide inputs is correct around the order of Fr.
+    #[test]
+    fn fr_from_wide_be_bytes_near_order() {
+        // Big endian bytes of the order of Fr, padded to 64 bytes.
+        let mut order = [0u8; 64];
+        let limbs = Fr::char().0;
+        for (i, limb) in limbs.iter().rev().enumerate() {
+            order[32 + 8 * i..32 + 8 * (i + 1)].copy_from_slice(&limb.to_be_bytes());
+        }
+        assert_eq!(fr_from_wide_be_bytes(&order), Fr::zero(), "r reduces to 0.");
+
+        let mut order_plus_one = order;
+        order_plus_one[63] += 1; // the order of Fr is odd
+        assert_eq!(
+            fr_from_wide_be_bytes(&order_plus_one),
+            Fr::one(),
+            "r + 1 reduces to 1."
+        );
+
+        let mut order_minus_one = order;
+        order_minus_one[63] -= 1;
+        let mut minus_one = Fr::one();
+        minus_one.negate();
+        assert_eq!(
+            fr_from_wide_be_bytes(&order_minus_one),
+            minus_one,
+            "r - 1 is -1."
+        );
+
+        // 2^256 + r - 1 = 2^256 - 1 mod r
+        let mut wide = order_minus_one;
+        wide[31] = 1;
+        let mut expected = Fr::from_repr(FrRepr([0, 0, 1, 0])).unwrap();
+        expected.square();
+        expected.sub_assign(&Fr::one());
+        assert_eq!(
+            fr_from_wide_be_bytes(&wide),
+            expected,
+            "2^256 + r - 1 reduces to 2^256 - 1."
+        );
+    }
+
+    #[test]
+    fn hash_to_scalar_deterministic() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let m: [u8; 32] = rng.gen();
+            let s1 = G1::hash_to_scalar(&m);
+            assert_eq!(
+                s1,
+                G1::hash_to_scalar(&m),
+                "Hashing should be deterministic."
+            );
+            assert_eq!(s1, G2::hash_to_scalar(&m), "G1 and G2 should agree.");
+            assert_ne!(
+                s1,
+                G1::hash_to_scalar(&m[1..]),
+                "Different inputs should differ."
+            );
+        }
+    }
+
+    #[test]
+    fn hash_to_scalar_fr_domain_separation() {
+        let h = |msg: &[u8], dst: &[u8]| hash_to_scalar_fr(msg, dst).expect("Valid DST.");
+        let s = h(b"message", b"DST-A");
+        assert_eq!(s, h(b"message", b"DST-A"), "Deterministic.");
+        assert_ne!(s, h(b"massage", b"DST-A"), "Other message.");
+        assert_ne!(s, h(b"message", b"DST-B"), "Other DST.");
+        assert_eq!(
+            G1::hash_to_scalar(b"message"),
+            h(b"message", HASH_TO_SCALAR_DST)
+        );
+        assert!(hash_to_scalar_fr(b"message", &[b'A'; 255]).is_ok());
+        assert_eq!(
+            hash_to_scalar_fr(b"message", &[b'A'; 256]),
+            Err(ExpandMessageError::DstTooLong(256))
+        );
+        // The outputs should span the field. The top limb of the modulus is
+        // 0x73ed..., so the top nibble of a uniform scalar takes all values
+        // from 0 to 7, and the lowest bit is 0 or 1 about equally often.
+        let mut top_nibbles = [0usize; 16];
+        let mut odd = 0;
+        for i in 0u32..1000 {
+            let repr = h(&i.to_be_bytes(), b"DST-A").into_repr().0;
+            top_nibbles[(repr[3] >> 60) as usize] += 1;
+            odd += (repr[0] & 1) as usize;
+        }
+        assert!(top_nibbles[..8].iter().all(|&n| n > 0), "{:?}", top_nibbles);
+        assert!(
+            top_nibbles[8..].iter().all(|&n| n == 0),
+            "{:?}",
+            top_nibbles
+        );
+        assert!((400..600).contains(&odd), "Odd scalars: {}", odd);
+    }
+
+    #[test]
+    fn base_from_be_bytes_vectors() {
+        // u[0].c0 for the empty message from
+        // https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#appendix-J.10.1
+        let bytes = hex::decode(
+            "03dbc2cce174e91ba93cbb08f26b917f98194a2ea08d1cce75b2b9cc9f21689d80bd79b594a613d0a68eb807dfdc1cf8",
+        )
+        .unwrap();
+        let expected = Fq::from_str("593868448310005448561172252387029516360409945786457439875974315031640021389835649561235021338510064922970633805048").unwrap();
+        assert_eq!(G1::base_from_be_bytes(&bytes).unwrap(), expected);
+        // Leading zeros do not change the value.
+        let mut padded = vec![0u8; 80];
+        padded.extend_from_slice(&bytes);
+        assert_eq!(G2::base_from_be_bytes(&padded).unwrap(), expected);
+        // The modulus reduces to zero, and the modulus plus one to one.
+        let mut q = hex::decode(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+        )
+        .unwrap();
+        assert_eq!(G1::base_from_be_bytes(&q).unwrap(), Fq::zero());
+        *q.last_mut().unwrap() += 1;
+        assert_eq!(G1::base_from_be_bytes(&q).unwrap(), Fq::one());
+        assert_eq!(
+            G1::base_from_be_bytes(&[7]).unwrap(),
+            Fq::from_str("7").unwrap()
+        );
+        assert!(
+            G1::base_from_be_bytes(&[]).is_err(),
+            "Empty input is rejected."
+        );
+    }
+
+    #[test]
+    fn derive_generators_reproducible() {
+        let gs = derive_generators_g1(b"seed", 20);
+        assert_eq!(gs.len(), 20);
+        assert_eq!(
+            gs,
+            derive_generators_g1(b"seed", 20),
+            "Same seed, same generators."
+        );
+        assert_eq!(
+            gs[..5],
+            derive_generators_g1(b"seed", 5)[..],
+            "Fewer generators should be a prefix."
+        );
+        for (i, g) in gs.iter().enumerate() {
+            assert!(!g.is_zero_point(), "Generators should be non-zero.");
+            assert!(!gs[..i].contains(g), "Generators should be distinct.");
+        }
+        let hs = derive_generators_g1(b"other seed", 20);
+        assert!(
+            hs.iter().all(|h| !gs.contains(h)),
+            "Different seeds should give disjoint generators."
+        );
+
+        let gs = derive_generators_g2(b"seed", 5);
+        assert_eq!(
+            gs,
+            derive_generators_g2(b"seed", 5),
+            "Same seed, same generators."
+        );
+        let hs = derive_generators_g2(b"other seed", 5);
+        assert!(
+            hs.iter().all(|h| !gs.contains(h)),
+            "Different seeds should give disjoint generators."
+        );
+    }
+
+    // Check that equality of points in projective coordinates is equality of
+    // the represented points, and not of the coordinates.
+    #[test]
+    fn points_equal_projective() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let g = G1::generate(&mut rng);
+            let h = g.double_point().plus_point(&g);
+            let h_normalized = h.into_affine().into_projective();
+            assert!(h.points_equal(&h_normalized), "Same point in G1.");
+            assert_eq!(h, h_normalized);
+            assert!(!h.points_equal(&g), "Different points in G1.");
+
+            let g = G2::generate(&mut rng);
+            let h = g.double_point().plus_point(&g);
+            let h_normalized = h.into_affine().into_projective();
+            assert!(h.points_equal(&h_normalized), "Same point in G2.");
+            assert_eq!(h, h_normalized);
+            assert!(!h.points_equal(&g), "Different points in G2.");
+        }
+    }
+
+    fn test_normalize_sign_generic<C: Curve>() {
+        let mut rng = rand::thread_rng();
+        assert!(C::zero_point().is_canonical_sign());
+        assert_eq!(C::zero_point().normalize_sign(), C::zero_point());
+        for _ in 0..100 {
+            let p = C::generate(&mut rng);
+            let n = p.normalize_sign();
+            assert!(n.is_canonical_sign());
+            assert_eq!(n.normalize_sign(), n, "Normalization is idempotent.");
+            assert_eq!(
+                p.inverse_point().normalize_sign(),
+                n,
+                "A point and its inverse have the same representative."
+            );
+            assert_ne!(
+                p.is_canonical_sign(),
+                p.inverse_point().is_canonical_sign(),
+                "Exactly one of a point and its inverse is canonical."
+            );
+        }
+    }
+
+    #[test]
+    fn normalize_sign() {
+        test_normalize_sign_generic::<G1>();
+        test_normalize_sign_generic::<G2>();
+        test_normalize_sign_generic::<G1Affine>();
+        test_normalize_sign_generic::<G2Affine>();
+    }
+
+    fn test_modulus_bytes_generic<C: Curve<Scalar = Fr, Base = Fq>>() {
+        use ff::PrimeFieldRepr;
+        let mut r = Vec::new();
+        Fr::char()
+            .write_be(&mut r)
+            .expect("Writing to a vector succeeds.");
+        let mut q = Vec::new();
+        Fq::char()
+            .write_be(&mut q)
+            .expect("Writing to a vector succeeds.");
+        assert_eq!(C::scalar_modulus_bytes(), &r[..]);
+        assert_eq!(C::base_modulus_bytes(), &q[..]);
+        assert_eq!(C::scalar_modulus_bytes().len(), C::SCALAR_LENGTH);
+        assert_eq!(
+            hex::encode(C::scalar_modulus_bytes()),
+            "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"
+        );
+        assert_eq!(
+            hex::encode(C::base_modulus_bytes()),
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab"
+        );
+    }
+
+    #[test]
+    fn modulus_bytes() {
+        test_modulus_bytes_generic::<G1>();
+        test_modulus_bytes_generic::<G2>();
+        test_modulus_bytes_generic::<G1Affine>();
+        test_modulus_bytes_generic::<G2Affine>();
+    }
+
     macro_rules! macro_test_scalar_byte_conversion {
         ($function_name:ident, $p:path) => {
             #[test]
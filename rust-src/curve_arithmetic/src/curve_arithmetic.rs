@@ -1,7 +1,8 @@
 use byteorder::ReadBytesExt;
-use crypto_common::{Serial, Serialize};
+use crypto_common::{hash_input::HashInput, Serial, Serialize};
 use ff::{Field, PrimeField};
 use rand::*;
+use rayon::iter::*;
 use std::{
     borrow::Borrow,
     fmt::{Debug, Display},
@@ -12,6 +13,14 @@ use thiserror::Error;
 pub enum CurveDecodingError {
     #[error("Not a point on the curve.")]
     NotOnCurve,
+    #[error("The point is the identity of the group.")]
+    IdentityPoint,
+}
+
+#[derive(Error, Debug)]
+pub enum FieldDecodingError {
+    #[error("Cannot decode a field element from empty input.")]
+    EmptyInput,
 }
 
 /// A relatively large trait that covers what is needed to perform constructions
@@ -36,9 +45,41 @@ pub trait Curve:
     /// Chosen generator of the group.
     fn one_point() -> Self;
     fn is_zero_point(&self) -> bool;
+    /// Check whether two values represent the same group element.
+    /// Implementations must ensure that [PartialEq] agrees with this, i.e.,
+    /// that it does not compare internal representations such as projective
+    /// coordinates. This is the case for the BLS12-381 instances, so the
+    /// default implementation defers to [PartialEq].
+    fn points_equal(&self, other: &Self) -> bool { self == other }
+    /// Check that the point is not the identity of the group. This is for use
+    /// in protocol steps that are not sound if given the identity point.
+    fn require_non_identity(&self) -> Result<(), CurveDecodingError> {
+        if self.is_zero_point() {
+            Err(CurveDecodingError::IdentityPoint)
+        } else {
+            Ok(())
+        }
+    }
     #[must_use]
     /// Return the group inverse of the given element.
     fn inverse_point(&self) -> Self;
+    /// Check whether the y-coordinate of the point, in affine form, has sign
+    /// 0 in the sense of the `sgn0` function of the hash-to-curve
+    /// specification. Of a point and its inverse exactly one has this
+    /// property, unless the point is its own inverse. The identity point is
+    /// considered canonical.
+    fn is_canonical_sign(&self) -> bool;
+    #[must_use]
+    /// Return the point or its inverse, whichever has the canonical sign, see
+    /// [Curve::is_canonical_sign]. This is the same for a point and its
+    /// inverse.
+    fn normalize_sign(&self) -> Self {
+        if self.is_canonical_sign() {
+            *self
+        } else {
+            self.inverse_point()
+        }
+    }
     #[must_use]
     /// Given x compute x + x.
     fn double_point(&self) -> Self;
@@ -53,6 +94,19 @@ pub trait Curve:
     /// Exponentiation by a scalar, i.e., compute n * x for a group element x
     /// and integer n.
     fn mul_by_scalar(&self, scalar: &Self::Scalar) -> Self;
+    /// Sum all the points of the iterator. The sum of no points is
+    /// [Curve::zero_point].
+    fn sum_points<'a, I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::zero_point(), |acc, x| acc.plus_point(x))
+    }
+    /// Same as [Curve::sum_points], but the sum is computed in parallel. This
+    /// is only worth it for large inputs.
+    fn sum_points_par(points: &[Self]) -> Self {
+        points
+            .par_iter()
+            .fold(Self::zero_point, |acc, x| acc.plus_point(x))
+            .reduce(Self::zero_point, |x, y| x.plus_point(&y))
+    }
     #[must_use]
     fn compress(&self) -> Self::Compressed;
     fn decompress(c: &Self::Compressed) -> Result<Self, CurveDecodingError>;
@@ -81,8 +135,22 @@ pub trait Curve:
     /// Make a scalar by taking the first Scalar::CAPACITY bits and interpreting
     /// them as a little-endian integer.
     fn scalar_from_bytes<A: AsRef<[u8]>>(bs: A) -> Self::Scalar;
+    /// Interpret the bytes as a big-endian integer and reduce it modulo the
+    /// characteristic of the [Curve::Base] field. Input of any non-zero length
+    /// is accepted.
+    fn base_from_be_bytes(bytes: &[u8]) -> Result<Self::Base, FieldDecodingError>;
     /// Hash to a curve point from a seed. This is deterministic function.
     fn hash_to_group(m: &[u8]) -> Self;
+    /// Hash bytes to a scalar. This is a deterministic function whose output
+    /// is (close to) uniformly distributed in the scalar field, in contrast to
+    /// [Curve::scalar_from_bytes] which merely truncates its input. It is
+    /// intended for deriving challenges in proofs.
+    fn hash_to_scalar(m: &[u8]) -> Self::Scalar;
+    /// The order of the group, i.e., the characteristic of [Curve::Scalar], as
+    /// a big-endian integer of [Curve::SCALAR_LENGTH] bytes.
+    fn scalar_modulus_bytes() -> &'static [u8];
+    /// The characteristic of the [Curve::Base] field as a big-endian integer.
+    fn base_modulus_bytes() -> &'static [u8];
 }
 
 /// A pairing friendly curve is a collection of two groups and a pairing
@@ -114,13 +182,20 @@ pub trait Pairing: Sized + 'static + Clone {
     /// Check whether the pairing equation holds given the left and right-hand
     /// sides.
     fn check_pairing_eq(g1x: &Self::G1, g2x: &Self::G2, g1y: &Self::G1, g2y: &Self::G2) -> bool {
-        let pairs = [
-            (&Self::g1_prepare(g1x), &Self::g2_prepare(g2x)),
-            (
-                &Self::g1_prepare(&g1y.inverse_point()),
-                &Self::g2_prepare(g2y),
-            ),
-        ];
+        Self::pairing_check(&[(*g1x, *g2x), (g1y.inverse_point(), *g2y)])
+    }
+
+    /// Check whether the product of the pairings of the given terms is the
+    /// identity of the target group. This uses a single Miller loop over all
+    /// the terms and a single final exponentiation, and avoids comparing
+    /// elements of the target field directly. The empty product is the
+    /// identity.
+    fn pairing_check(terms: &[(Self::G1, Self::G2)]) -> bool {
+        let prepared = terms
+            .iter()
+            .map(|(g1, g2)| (Self::g1_prepare(g1), Self::g2_prepare(g2)))
+            .collect::<Vec<_>>();
+        let pairs = prepared.iter().map(|(g1, g2)| (g1, g2)).collect::<Vec<_>>();
         let res = Self::miller_loop(pairs.iter());
         if let Some(mut y) = Self::final_exponentiation(&res) {
             y.sub_assign(&Self::TargetField::one());
@@ -174,6 +249,18 @@ pub trait Pairing: Sized + 'static + Clone {
     }
 }
 
+/// Hashing of a [HashInput] to a scalar. This is a trait only because
+/// [HashInput] is defined in `crypto_common`, which cannot refer to [Curve].
+pub trait HashInputToScalar {
+    /// Hash the encoded segments to a scalar of the curve `C` with
+    /// [Curve::hash_to_scalar].
+    fn finalize_to_scalar<C: Curve>(&self) -> C::Scalar;
+}
+
+impl HashInputToScalar for HashInput {
+    fn finalize_to_scalar<C: Curve>(&self) -> C::Scalar { C::hash_to_scalar(self.as_bytes()) }
+}
+
 /// Like 'multiexp_worker', but computes a reasonable window size automatically.
 #[inline(always)]
 pub fn multiexp<C: Curve, X: Borrow<C>>(gs: &[X], exps: &[C::Scalar]) -> C {
@@ -301,7 +388,7 @@ pub fn multiexp_table<C: Curve, X: Borrow<C>>(gs: &[X], window_size: usize) -> V
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pairing::bls12_381::G1;
+    use pairing::bls12_381::{G1, G2};
 
     #[test]
     pub fn test_multiscalar() {
@@ -325,4 +412,37 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    pub fn test_require_non_identity() {
+        let mut csprng = thread_rng();
+        assert!(matches!(
+            G1::zero_point().require_non_identity(),
+            Err(CurveDecodingError::IdentityPoint)
+        ));
+        assert!(matches!(
+            G2::zero_point().require_non_identity(),
+            Err(CurveDecodingError::IdentityPoint)
+        ));
+        assert!(G1::one_point().require_non_identity().is_ok());
+        assert!(G2::generate(&mut csprng).require_non_identity().is_ok());
+    }
+
+    #[test]
+    pub fn test_sum_points() {
+        let mut csprng = thread_rng();
+        assert_eq!(G1::sum_points([].iter()), G1::zero_point());
+        assert_eq!(G1::sum_points_par(&[]), G1::zero_point());
+        for l in [1, 2, 17, 1000].iter() {
+            let gs = (0..*l)
+                .map(|_| G1::generate(&mut csprng))
+                .collect::<Vec<_>>();
+            let mut expected = G1::zero_point();
+            for g in gs.iter() {
+                expected = expected.plus_point(g);
+            }
+            assert_eq!(G1::sum_points(gs.iter()), expected, "Sequential sum.");
+            assert_eq!(G1::sum_points_par(&gs), expected, "Parallel sum.");
+        }
+    }
 }
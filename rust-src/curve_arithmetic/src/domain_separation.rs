@@ -0,0 +1,100 @@
+//! Canonical domain separation strings (DSTs) for hashing to curves and
+//! fields.
+
+use sha2::{Digest, Sha256};
+
+/// Prefix of the hash of a DST that is too long, see
+/// https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-5.4.3
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+/// The maximal length of a DST.
+const MAX_DST_LENGTH: usize = 255;
+
+/// The components of a domain separation string. The string is
+///
+/// `<app_name>-V<protocol_version>-with-<suite_id>`
+///
+/// where the version is written in decimal with at least two digits, e.g.,
+/// `CONCORDIUM-V01-with-BLS12381G1_XMD:SHA-256_SSWU_RO_`. This follows the
+/// recommendations in
+/// https://tools.ietf.org/html/draft-irtf-cfrg-hash-to-curve-10#section-3.1
+/// The application name should not contain `-V`, so that the string can be
+/// split into its components unambiguously.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DomainSeparation<'a> {
+    pub app_name:         &'a str,
+    pub protocol_version: u16,
+    pub suite_id:         &'a str,
+}
+
+impl<'a> DomainSeparation<'a> {
+    pub fn new(app_name: &'a str, protocol_version: u16, suite_id: &'a str) -> Self {
+        DomainSeparation {
+            app_name,
+            protocol_version,
+            suite_id,
+        }
+    }
+
+    /// The DST in the format described above. If that is longer than 255
+    /// bytes, the maximal length of a DST, then it is replaced by its SHA-256
+    /// hash prefixed by `H2C-OVERSIZE-DST-`, as the specification prescribes.
+    /// The result can thus always be used as a DST.
+    pub fn build(&self) -> Vec<u8> {
+        let dst = format!(
+            "{}-V{:02}-with-{}",
+            self.app_name, self.protocol_version, self.suite_id
+        )
+        .into_bytes();
+        if dst.len() <= MAX_DST_LENGTH {
+            dst
+        } else {
+            Sha256::new()
+                .chain_update(OVERSIZE_DST_PREFIX)
+                .chain_update(&dst)
+                .finalize()
+                .to_vec()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let dst = DomainSeparation::new("CONCORDIUM", 1, "BLS12381G1_XMD:SHA-256_SSWU_RO_");
+        assert_eq!(
+            dst.build(),
+            b"CONCORDIUM-V01-with-BLS12381G1_XMD:SHA-256_SSWU_RO_".to_vec()
+        );
+        let bumped = DomainSeparation {
+            protocol_version: 2,
+            ..dst
+        };
+        assert_eq!(
+            bumped.build(),
+            b"CONCORDIUM-V02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_".to_vec()
+        );
+        assert_eq!(
+            DomainSeparation::new("CONCORDIUM", 300, "X").build(),
+            b"CONCORDIUM-V300-with-X".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_build_oversize() {
+        let app_name = "A".repeat(237);
+        // The DST is exactly 255 bytes.
+        let dst = DomainSeparation::new(&app_name, 1, "12345678");
+        assert_eq!(dst.build().len(), 255);
+        let long = DomainSeparation::new(&app_name, 1, "123456789");
+        let mut expected = Sha256::new();
+        expected.update(b"H2C-OVERSIZE-DST-");
+        expected.update(format!("{}-V01-with-123456789", app_name).as_bytes());
+        assert_eq!(long.build(), expected.finalize().to_vec());
+        // The result is accepted as a DST.
+        assert!(crate::hash_to_scalar_fr(b"message", &long.build()).is_ok());
+    }
+}
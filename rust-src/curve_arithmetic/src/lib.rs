@@ -4,10 +4,24 @@ mod bls12_381_g1hash;
 mod bls12_381_g2hash;
 mod bls12_381_instance;
 mod curve_arithmetic;
-pub use crate::curve_arithmetic::*;
+pub use crate::{
+    bls12_381_g1hash::ExpandMessageError,
+    bls12_381_instance::{
+        derive_generators_g1, derive_generators_g2, hash_to_scalar_fr, HASH_TO_SCALAR_DST,
+    },
+    curve_arithmetic::*,
+};
 
 pub mod secret_value;
 pub use secret_value::{Secret, Value};
 
+pub mod point;
+pub use point::Point;
+
+pub mod domain_separation;
+pub use domain_separation::DomainSeparation;
+
+pub mod secret_sharing;
+
 #[macro_use]
 extern crate crypto_common_derive;
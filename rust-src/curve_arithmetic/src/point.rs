@@ -0,0 +1,77 @@
+// -*- mode: rust; -*-
+
+//! A thin wrapper around a group element that supports arithmetic operators.
+
+use crate::curve_arithmetic::*;
+use crypto_common::*;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A group element with arithmetic operators. This is intended to make
+/// algorithms easier to read, all the operations delegate to the corresponding
+/// methods of the [Curve] trait.
+#[repr(transparent)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct Point<C: Curve> {
+    pub point: C,
+}
+
+impl<C: Curve> From<C> for Point<C> {
+    fn from(point: C) -> Self { Point { point } }
+}
+
+impl<C: Curve> Point<C> {
+    pub fn new(point: C) -> Self { Point { point } }
+
+    /// Unwrap the underlying group element.
+    pub fn into_inner(self) -> C { self.point }
+}
+
+impl<C: Curve> Add for Point<C> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self { self.point.plus_point(&rhs.point).into() }
+}
+
+impl<C: Curve> Sub for Point<C> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self { self.point.minus_point(&rhs.point).into() }
+}
+
+impl<C: Curve> Neg for Point<C> {
+    type Output = Self;
+
+    fn neg(self) -> Self { self.point.inverse_point().into() }
+}
+
+impl<C: Curve> Mul<C::Scalar> for Point<C> {
+    type Output = Self;
+
+    fn mul(self, rhs: C::Scalar) -> Self { self.point.mul_by_scalar(&rhs).into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::G1;
+    use rand::*;
+
+    #[test]
+    pub fn test_operators_g1() {
+        let mut csprng = thread_rng();
+        for _ in 0..100 {
+            let a = G1::generate(&mut csprng);
+            let b = G1::generate(&mut csprng);
+            let s = G1::generate_scalar(&mut csprng);
+            let (pa, pb) = (Point::from(a), Point::from(b));
+            assert_eq!((pa + pb).point, a.plus_point(&b), "Addition.");
+            assert_eq!((pa - pb).point, a.minus_point(&b), "Subtraction.");
+            assert_eq!((-pa).point, a.inverse_point(), "Negation.");
+            assert_eq!(
+                (pa * s).point,
+                a.mul_by_scalar(&s),
+                "Scalar multiplication."
+            );
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Shamir secret sharing of scalars. A secret is shared as the evaluations of a
+//! random polynomial of degree `threshold - 1` whose constant term is the
+//! secret, so that any `threshold` shares determine it, and fewer reveal
+//! nothing about it.
+use crate::Curve;
+use ff::Field;
+use rand::*;
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+/// Reasons why [reconstruct] cannot recover the secret.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum SecretSharingError {
+    #[error("Got {got} shares, but at least {threshold} are needed.")]
+    NotEnoughShares { got: usize, threshold: usize },
+    #[error("Share with index {0} appears more than once.")]
+    DuplicateIndex(u64),
+}
+
+/// Split the secret into `n` shares, any `threshold` of which suffice to
+/// reconstruct it. The shares are the evaluations of the sharing polynomial at
+/// the indices `1..=n`, paired with the index.
+///
+/// # Panics
+/// If `threshold` is 0 or larger than `n`.
+pub fn share<C: Curve, R: Rng>(
+    secret: &C::Scalar,
+    threshold: usize,
+    n: usize,
+    csprng: &mut R,
+) -> Vec<(u64, C::Scalar)> {
+    assert!(
+        threshold >= 1 && threshold <= n,
+        "Threshold must be between 1 and the number of shares."
+    );
+    // The highest coefficient is non-zero so that the polynomial has exactly
+    // degree threshold - 1.
+    let mut coefficients = (2..threshold)
+        .map(|_| C::generate_scalar(csprng))
+        .collect::<Vec<_>>();
+    if threshold > 1 {
+        coefficients.push(C::generate_non_zero_scalar(csprng));
+    }
+    (1..=n as u64)
+        .map(|i| {
+            let x = C::scalar_from_u64(i);
+            // Horner's method, with the secret as the constant term.
+            let mut y = C::Scalar::zero();
+            for coeff in coefficients.iter().rev() {
+                y.add_assign(coeff);
+                y.mul_assign(&x);
+            }
+            y.add_assign(secret);
+            (i, y)
+        })
+        .collect()
+}
+
+/// The Lagrange coefficient of the share with index `i` when interpolating at
+/// zero from shares with the given `indices`. The indices must be distinct
+/// and contain `i`.
+pub fn lagrange_at_zero<C: Curve>(indices: &[u64], i: u64) -> C::Scalar {
+    let x_i = C::scalar_from_u64(i);
+    let mut num = C::Scalar::one();
+    let mut den = C::Scalar::one();
+    for &j in indices.iter().filter(|&&j| j != i) {
+        let x_j = C::scalar_from_u64(j);
+        num.mul_assign(&x_j);
+        let mut diff = x_j;
+        diff.sub_assign(&x_i);
+        den.mul_assign(&diff);
+    }
+    num.mul_assign(&den.inverse().expect("Indices are distinct."));
+    num
+}
+
+/// Recover the secret from shares produced by [share] with the given
+/// threshold, by Lagrange interpolation at zero. All given shares are used.
+pub fn reconstruct<C: Curve>(
+    shares: &[(u64, C::Scalar)],
+    threshold: usize,
+) -> Result<C::Scalar, SecretSharingError> {
+    if shares.len() < threshold {
+        return Err(SecretSharingError::NotEnoughShares {
+            got: shares.len(),
+            threshold,
+        });
+    }
+    let mut seen = BTreeSet::new();
+    for &(i, _) in shares {
+        if !seen.insert(i) {
+            return Err(SecretSharingError::DuplicateIndex(i));
+        }
+    }
+    let indices = shares.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+    Ok(shares.iter().fold(C::Scalar::zero(), |mut acc, (i, y)| {
+        let mut term = lagrange_at_zero::<C>(&indices, *i);
+        term.mul_assign(y);
+        acc.add_assign(&term);
+        acc
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::G1;
+    use rand::seq::SliceRandom;
+
+    #[test]
+    fn share_and_reconstruct() {
+        let mut csprng = thread_rng();
+        for n in 1..8 {
+            for threshold in 1..=n {
+                let secret = G1::generate_scalar(&mut csprng);
+                let mut shares = share::<G1, _>(&secret, threshold, n, &mut csprng);
+                assert_eq!(shares.len(), n);
+                shares.shuffle(&mut csprng);
+                assert_eq!(
+                    reconstruct::<G1>(&shares[..threshold], threshold),
+                    Ok(secret),
+                    "Exactly threshold shares suffice."
+                );
+                assert_eq!(reconstruct::<G1>(&shares, threshold), Ok(secret));
+                assert_eq!(
+                    reconstruct::<G1>(&shares[..threshold - 1], threshold),
+                    Err(SecretSharingError::NotEnoughShares {
+                        got: threshold - 1,
+                        threshold,
+                    })
+                );
+                if threshold > 1 {
+                    // Without the threshold check the result is some other
+                    // value than the secret.
+                    assert_ne!(
+                        reconstruct::<G1>(&shares[..threshold - 1], threshold - 1),
+                        Ok(secret)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reconstruct_duplicate_index() {
+        let mut csprng = thread_rng();
+        let secret = G1::generate_scalar(&mut csprng);
+        let mut shares = share::<G1, _>(&secret, 3, 5, &mut csprng);
+        shares[1] = shares[0];
+        assert_eq!(
+            reconstruct::<G1>(&shares[..3], 3),
+            Err(SecretSharingError::DuplicateIndex(1))
+        );
+    }
+}
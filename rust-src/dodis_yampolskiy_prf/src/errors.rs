@@ -9,6 +9,12 @@ use thiserror::Error;
 pub(crate) enum InternalError {
     #[error("Division by zero.")]
     DivisionByZero,
+    #[error("Wrong length of the key: expected {expected} bytes, got {got}.")]
+    WrongLength { expected: usize, got: usize },
+    #[error("The key is not a canonical encoding of a scalar.")]
+    NonCanonical,
+    #[error("The key is zero.")]
+    ZeroKey,
 }
 
 /// Errors which may occur while processing proofs and keys.
@@ -4,6 +4,7 @@
 mod errors;
 mod secret;
 
+pub use errors::PrfError;
 pub use secret::*;
 
 #[macro_use]
@@ -1,11 +1,12 @@
 //! PRF Key type
 
-use crate::errors::{InternalError::DivisionByZero, *};
+use crate::errors::{InternalError::*, *};
 use crypto_common::*;
 use curve_arithmetic::{Curve, Secret, Value};
 use ff::Field;
 use rand::*;
 use std::rc::Rc;
+use zeroize::Zeroizing;
 
 /// A PRF key.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, SerdeBase16Serialize)]
@@ -50,7 +51,12 @@ impl<C: Curve> SecretKey<C> {
     /// If this function returns OK(_) then the [SecretKey::prf] would also
     /// return Ok, and vice-versa.
     pub fn prf_exponent(&self, n: u8) -> Result<C::Scalar, PrfError> {
-        let mut x = C::scalar_from_u64(u64::from(n));
+        self.prf_exponent_u64(u64::from(n))
+    }
+
+    /// Compute `1/(key + input)`, failing if the denominator is zero.
+    fn prf_exponent_u64(&self, input: u64) -> Result<C::Scalar, PrfError> {
+        let mut x = C::scalar_from_u64(input);
         x.add_assign(self);
         match x.inverse() {
             None => Err(PrfError(DivisionByZero)),
@@ -64,6 +70,41 @@ impl<C: Curve> SecretKey<C> {
         Ok(g.mul_by_scalar(&y))
     }
 
+    /// Evaluate the PRF on the given input with the chosen generator of the
+    /// group, i.e., compute `1/(key + input) * C::one_point()`. This fails if
+    /// `key + input` is zero. In contrast to [SecretKey::prf] the input is not
+    /// restricted to a single byte.
+    pub fn evaluate(&self, input: u64) -> Result<C, PrfError> {
+        let y = self.prf_exponent_u64(input)?;
+        Ok(C::one_point().mul_by_scalar(&y))
+    }
+
+    /// Serialize the key as the canonical big-endian encoding of the scalar,
+    /// into a buffer that is zeroed when dropped.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(Vec::with_capacity(C::SCALAR_LENGTH));
+        self.0.serial(&mut *out);
+        out
+    }
+
+    /// Parse a key from exactly [Curve::SCALAR_LENGTH] bytes, as produced by
+    /// [SecretKey::to_bytes]. Non-canonical encodings and the zero key are
+    /// rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SecretKey<C>, PrfError> {
+        if bytes.len() != C::SCALAR_LENGTH {
+            return Err(PrfError(WrongLength {
+                expected: C::SCALAR_LENGTH,
+                got:      bytes.len(),
+            }));
+        }
+        let scalar: C::Scalar =
+            from_bytes(&mut std::io::Cursor::new(bytes)).map_err(|_| PrfError(NonCanonical))?;
+        if scalar.is_zero() {
+            return Err(PrfError(ZeroKey));
+        }
+        Ok(SecretKey::new(scalar))
+    }
+
     /// Generate a `SecretKey` from a `csprng`.
     pub fn generate<T>(csprng: &mut T) -> SecretKey<C>
     where
@@ -87,4 +128,70 @@ mod tests {
             assert_eq!(sk2, sk);
         }
     }
+
+    #[test]
+    pub fn evaluate_distinct_and_degenerate() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate_non_zero(&mut csprng);
+        let outputs = (0..50u64)
+            .map(|i| {
+                sk.evaluate(i)
+                    .expect("Key is random, so key + i is non-zero.")
+            })
+            .collect::<Vec<_>>();
+        for (i, x) in outputs.iter().enumerate() {
+            assert!(!outputs[..i].contains(x), "Outputs should be distinct.");
+        }
+        assert_eq!(
+            sk.evaluate(7).unwrap(),
+            sk.prf(&G1::one_point(), 7).unwrap(),
+            "Evaluation agrees with prf on the chosen generator."
+        );
+        let mut minus_five = G1::scalar_from_u64(5);
+        minus_five.negate();
+        let degenerate = SecretKey::<G1>::new(minus_five);
+        assert!(matches!(
+            degenerate.evaluate(5),
+            Err(PrfError(DivisionByZero))
+        ));
+        assert!(degenerate.evaluate(6).is_ok());
+    }
+
+    #[test]
+    pub fn key_bytes_roundtrip() {
+        let mut csprng = thread_rng();
+        for _ in 1..100 {
+            let sk = SecretKey::<G1>::generate_non_zero(&mut csprng);
+            let bytes = sk.to_bytes();
+            assert_eq!(bytes.len(), G1::SCALAR_LENGTH);
+            let sk2 = SecretKey::<G1>::from_bytes(&bytes).expect("Valid key.");
+            assert_eq!(sk2, sk);
+        }
+        assert!(matches!(
+            SecretKey::<G1>::from_bytes(&[0u8; 32]),
+            Err(PrfError(ZeroKey))
+        ));
+        assert!(matches!(
+            SecretKey::<G1>::from_bytes(&[0xffu8; 32]),
+            Err(PrfError(NonCanonical))
+        ));
+        assert!(matches!(
+            SecretKey::<G1>::from_bytes(&[1u8; 31]),
+            Err(PrfError(WrongLength {
+                expected: 32,
+                got:      31,
+            }))
+        ));
+    }
+
+    /// Zeroizing on drop only clears the final allocation, so the buffer must
+    /// be allocated with its exact size and never grow.
+    #[test]
+    pub fn key_bytes_exact_capacity() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate_non_zero(&mut csprng);
+        let bytes = sk.to_bytes();
+        assert_eq!(bytes.len(), G1::SCALAR_LENGTH);
+        assert_eq!(bytes.capacity(), bytes.len(), "The buffer should not grow.");
+    }
 }
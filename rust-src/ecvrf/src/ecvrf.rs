@@ -1,3 +1,4 @@
+use crate::constants::*;
 pub use crate::{errors::*, proof::*, public::*, secret::*};
 use crypto_common::*;
 use rand::{CryptoRng, Rng};
@@ -25,6 +26,39 @@ impl Keypair {
         }
     }
 
+    /// Construct a keypair from the bytes of the secret key followed by the
+    /// bytes of the public key.
+    ///
+    /// Absolutely no validation is done that the public key corresponds to
+    /// the secret key. Use [Keypair::from_bytes_checked] unless the bytes are
+    /// known to come from a valid keypair.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Keypair, ProofError> {
+        if bytes.len() != KEYPAIR_LENGTH {
+            return Err(ProofError(InternalError::BytesLength {
+                name:   "Keypair",
+                length: KEYPAIR_LENGTH,
+            }));
+        }
+        let secret = SecretKey::from_bytes(&bytes[..SECRET_KEY_LENGTH])?;
+        let public =
+            from_bytes(&mut std::io::Cursor::new(&bytes[SECRET_KEY_LENGTH..])).map_err(|e| {
+                e.downcast::<ProofError>()
+                    .unwrap_or(ProofError(InternalError::PointDecompression))
+            })?;
+        Ok(Keypair { secret, public })
+    }
+
+    /// Same as [Keypair::from_bytes], but additionally check that the public
+    /// key is the one derived from the secret key.
+    pub fn from_bytes_checked(bytes: &[u8]) -> Result<Keypair, ProofError> {
+        let keypair = Self::from_bytes(bytes)?;
+        let expected = PublicKey::from(&keypair.secret);
+        if !ct::ct_eq_bytes(expected.as_bytes(), keypair.public.as_bytes()) {
+            return Err(ProofError(InternalError::KeypairMismatch));
+        }
+        Ok(keypair)
+    }
+
     /// Construct a VRF proof with this keypair's secret key.
     pub fn prove(&self, message: &[u8]) -> Proof {
         let expanded: ExpandedSecretKey = (&self.secret).into();
@@ -36,9 +70,8 @@ impl Keypair {
 #[cfg(feature = "ffi")]
 mod expose_ffi {
     use super::*;
-    use crypto_common::size_t;
+    use crypto_common::{rng::secure_rng, size_t};
     use ffi_helpers::*;
-    use rand::thread_rng;
     use std::{cmp::Ordering, sync::Arc};
     use subtle::ConstantTimeEq;
 
@@ -94,7 +127,7 @@ mod expose_ffi {
     /// Generate a new secret key using the system random number generator.
     /// The result is always a non-null pointer.
     extern "C" fn ecvrf_priv_key() -> *mut SecretKey {
-        let mut csprng = thread_rng();
+        let mut csprng = secure_rng();
         let sk = SecretKey::generate(&mut csprng);
         Box::into_raw(Box::new(sk))
     }
@@ -215,6 +248,92 @@ mod tests {
     use super::*;
 
     use curve25519_dalek::scalar::Scalar;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_keypair_from_bytes_checked() {
+        let mut csprng = thread_rng();
+        let keypair = Keypair::generate(&mut csprng);
+        let other = Keypair::generate(&mut csprng);
+        let bytes = to_bytes(&keypair);
+        let checked = Keypair::from_bytes_checked(&bytes).expect("Valid keypair should parse.");
+        assert_eq!(checked.public, keypair.public);
+
+        // Replace the public half with the public key of another keypair.
+        let mut mismatched = to_bytes(&keypair.secret);
+        mismatched.extend_from_slice(other.public.as_bytes());
+        assert!(
+            Keypair::from_bytes(&mismatched).is_ok(),
+            "Unchecked parsing does not validate the keypair."
+        );
+        assert_eq!(
+            Keypair::from_bytes_checked(&mismatched).unwrap_err(),
+            ProofError(InternalError::KeypairMismatch)
+        );
+    }
+
+    #[test]
+    fn test_challenge_bytes_deterministic() {
+        let keypair = Keypair::generate(&mut crypto_common::rng::seeded_rng(&[7u8; 32]));
+        let proof = keypair.prove(b"message");
+        let challenge = proof.challenge_bytes();
+        let other = Keypair::generate(&mut crypto_common::rng::seeded_rng(&[7u8; 32]));
+        assert_eq!(
+            other.prove(b"message").challenge_bytes(),
+            challenge,
+            "Same key and message give the same challenge."
+        );
+        assert_ne!(keypair.prove(b"other message").challenge_bytes(), challenge);
+        assert_eq!(challenge[16..], [0u8; 16], "The challenge has 16 bytes.");
+        assert_eq!(
+            challenge[..16],
+            to_bytes(&proof)[32..48],
+            "The challenge is part of the proof."
+        );
+    }
+
+    #[test]
+    fn test_proof_embedded() {
+        #[derive(Serialize, Debug, PartialEq)]
+        struct Embedded {
+            before: u32,
+            proof:  Proof,
+            after:  u8,
+        }
+        let mut csprng = thread_rng();
+        let keypair = Keypair::generate(&mut csprng);
+        let value = Embedded {
+            before: 17,
+            proof:  keypair.prove(b"message"),
+            after:  42,
+        };
+        let bytes = to_bytes(&value);
+        assert_eq!(
+            bytes.len(),
+            4 + PROOF_LENGTH + 1,
+            "The proof has no length prefix."
+        );
+        let parsed: Embedded =
+            from_bytes(&mut std::io::Cursor::new(&bytes)).expect("Embedded proof should parse.");
+        assert_eq!(parsed, value);
+        assert_eq!(
+            Proof::from_bytes(&bytes[4..4 + PROOF_LENGTH]).expect("Valid proof."),
+            value.proof
+        );
+        assert_eq!(
+            Proof::from_bytes(&bytes[4..]).unwrap_err(),
+            ProofError(InternalError::BytesLength {
+                name:   "Proof",
+                length: PROOF_LENGTH,
+            })
+        );
+        let mut bad_scalar = to_bytes(&value.proof);
+        bad_scalar[48..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            Proof::from_bytes(&bad_scalar).unwrap_err(),
+            ProofError(InternalError::ScalarFormat)
+        );
+    }
 
     /// Test against test vectors specified in
     /// https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.appendix.A.3
@@ -260,6 +379,7 @@ mod tests {
             let mut proof_bytes: Vec<u8> = Vec::new();
             proof.serial(&mut proof_bytes);
             assert!(proof_bytes.iter().eq(pi_bytes.iter()));
+            assert_eq!(proof.to_array(), pi_bytes);
 
             // Test hash of proof
             let p2h = proof.to_hash();
@@ -21,6 +21,9 @@ pub(crate) enum InternalError {
     /// The verification equation wasn't satisfied
     #[error("Verification equation was not satisfied.")]
     Verify,
+    /// The public key of a keypair does not correspond to its secret key.
+    #[error("Public key does not correspond to the secret key.")]
+    KeypairMismatch,
 }
 
 /// Errors which may occur while processing proofs and keypairs.
@@ -30,19 +30,30 @@ pub fn hash_points(pts: &[CompressedEdwardsY]) -> Scalar {
 pub struct Proof(pub EdwardsPoint, pub Scalar, pub Scalar);
 
 /// Implements step 8 of <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.section.5.1>
-/// i.e. transforms a proof to a byte string
+/// i.e. transforms a proof to a byte string. The proof is always
+/// [PROOF_LENGTH] bytes, so there is no length prefix. The layout is
+///
+/// - bytes 0..32: the compressed point `Gamma`,
+/// - bytes 32..48: the challenge `c`, as 16 little-endian bytes,
+/// - bytes 48..80: the scalar `s`, in canonical little-endian form.
 impl Serial for Proof {
     #[inline]
     fn serial<B: Buffer>(&self, x: &mut B) {
+        x.write_all(&self.to_array())
+            .expect("Writing to buffer should succeed.");
+    }
+}
+
+impl SerialFixed<PROOF_LENGTH> for Proof {
+    fn to_array(&self) -> [u8; PROOF_LENGTH] {
         let c = &self.1.reduce().to_bytes();
         // assert c is within range
         assert_eq!(c[16..32], [0u8; 16]);
-        x.write_all(&self.0.compress().to_bytes()[..])
-            .expect("Writing to buffer should succeed.");
-        x.write_all(&c[..16])
-            .expect("Writing to buffer should succeed.");
-        x.write_all(&self.2.reduce().to_bytes()[..])
-            .expect("Writing to buffer should succeed.");
+        let mut out = [0u8; PROOF_LENGTH];
+        out[..32].copy_from_slice(&self.0.compress().to_bytes());
+        out[32..48].copy_from_slice(&c[..16]);
+        out[48..].copy_from_slice(&self.2.reduce().to_bytes());
+        out
     }
 }
 
@@ -80,6 +91,28 @@ impl Debug for Proof {
 
 /// Implements <https://tools.ietf.org/id/draft-irtf-cfrg-vrf-07.html#rfc.section.5.2>
 impl Proof {
+    /// The canonical encoding of the Fiat-Shamir challenge `c` of the proof.
+    /// Only the first 16 bytes can be non-zero. The challenge is part of the
+    /// serialized proof, so this exposes no secret, but it is convenient when
+    /// comparing against other implementations.
+    pub fn challenge_bytes(&self) -> [u8; 32] { self.1.to_bytes() }
+
+    /// Parse a proof from exactly [PROOF_LENGTH] bytes in the layout written by
+    /// [Serial]. This checks that the point decompresses and that the scalars
+    /// are canonical.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Proof, ProofError> {
+        if bytes.len() != PROOF_LENGTH {
+            return Err(ProofError(InternalError::BytesLength {
+                name:   "Proof",
+                length: PROOF_LENGTH,
+            }));
+        }
+        from_bytes(&mut std::io::Cursor::new(bytes)).map_err(|e| {
+            e.downcast::<ProofError>()
+                .unwrap_or(ProofError(InternalError::PointDecompression))
+        })
+    }
+
     pub fn to_hash(&self) -> [u8; 64] {
         let p = self.0.mul_by_cofactor();
         let hash: Sha512 = Sha512::new()
@@ -155,7 +155,7 @@ impl PublicKey {
 
             let derivable_c =
                 hash_points(&[h.compress(), gamma.compress(), u.compress(), v.compress()]);
-            *c == derivable_c
+            ct::ct_eq_bytes(c.as_bytes(), derivable_c.as_bytes())
         } else {
             false
         }
@@ -1,9 +1,8 @@
 #![cfg(feature = "ffi")]
 use ed25519_dalek::*;
-use rand::*;
 
 use crate::dlog_ed25519::*;
-use crypto_common::*;
+use crypto_common::{rng::secure_rng, *};
 use ffi_helpers::*;
 use std::{convert::TryFrom, io::Cursor};
 
@@ -12,7 +11,7 @@ use random_oracle::RandomOracle;
 // foreign function interfacee
 #[no_mangle]
 extern "C" fn eddsa_priv_key() -> *mut SecretKey {
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     let sk = SecretKey::generate(&mut csprng);
     Box::into_raw(Box::new(sk))
 }
@@ -172,7 +171,7 @@ extern "C" fn eddsa_prove_dlog_ed25519(
         }
     };
     let proof_bytes = mut_slice_from_c_bytes!(proof_ptr, PROOF_LENGTH);
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     let proof = prove_dlog_ed25519(
         &mut csprng,
         &mut RandomOracle::domain(&challenge),
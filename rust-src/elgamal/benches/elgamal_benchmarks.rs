@@ -156,12 +156,29 @@ pub fn baby_step_giant_step_bench(c: &mut Criterion) {
     });
 }
 
+pub fn precomputed_encrypt_bench(c: &mut Criterion) {
+    let mut csprng = thread_rng();
+    let sk: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+    let pk = PublicKey::from(&sk);
+    let precomputed = pk.with_precomputation();
+    let m = Message::generate(&mut csprng);
+    let k = G1::generate_scalar(&mut csprng);
+    c.bench_function("encrypt with plain public key", move |b| {
+        b.iter(|| pk.hide(&k, &m))
+    });
+    let m = Message::generate(&mut csprng);
+    c.bench_function("encrypt with precomputed public key", move |b| {
+        b.iter(|| precomputed.hide(&k, &m))
+    });
+}
+
 criterion_group! {
     name = elgamal_benches;
     config = Criterion::default().measurement_time(Duration::from_millis(1000)).sample_size(2);
     targets =
         baby_step_giant_step_table_bench,
-        baby_step_giant_step_bench
+        baby_step_giant_step_bench,
+        precomputed_encrypt_bench
 }
 
 criterion_main!(elgamal_benches);
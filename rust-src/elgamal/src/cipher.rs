@@ -1,11 +1,14 @@
 //! Elgamal cipher  types
 
+use crate::errors::*;
 use crypto_common::*;
 use crypto_common_derive::*;
 use curve_arithmetic::*;
 
 use rand::*;
+use rayon::prelude::*;
 use std::ops::Deref;
+use subtle::{Choice, ConstantTimeEq};
 
 use std::rc::Rc;
 
@@ -64,6 +67,30 @@ impl<C: Curve> Cipher<C> {
         Ok(Cipher(g, h))
     }
 
+    /// Construct a cipher from exactly `2 * C::GROUP_ELEMENT_LENGTH` bytes.
+    /// The error distinguishes input of the wrong length from a component
+    /// that is not a valid group element.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Cipher<C>, ElgamalError> {
+        check_length(bytes, 2 * C::GROUP_ELEMENT_LENGTH)?;
+        let (g, h) = bytes.split_at(C::GROUP_ELEMENT_LENGTH);
+        Ok(Cipher(
+            decode_point(g, "first component of the cipher")?,
+            decode_point(h, "second component of the cipher")?,
+        ))
+    }
+
+    /// Decode a concatenation of ciphers, each of `2 * C::GROUP_ELEMENT_LENGTH`
+    /// bytes. On failure the error is paired with the index of the offending
+    /// cipher. If the input ends in an incomplete cipher, that is the index of
+    /// the incomplete cipher, with a length error.
+    pub fn from_bytes_many(bytes: &[u8]) -> Result<Vec<Cipher<C>>, (usize, ElgamalError)> {
+        bytes
+            .chunks(2 * C::GROUP_ELEMENT_LENGTH)
+            .enumerate()
+            .map(|(i, chunk)| Cipher::from_bytes(chunk).map_err(|e| (i, e)))
+            .collect()
+    }
+
     /// Generate a random cipher.
     pub fn generate<T>(csprng: &mut T) -> Self
     where
@@ -81,6 +108,25 @@ impl<C: Curve> Cipher<C> {
         Self(self.0.plus_point(&other.0), self.1.plus_point(&other.1))
     }
 
+    /// The cipher `(0, 0)`, which is the neutral element of
+    /// [combine](Self::combine). It decrypts to the zero message under any
+    /// key, i.e., to 0 in the exponent.
+    pub fn zero() -> Self { Cipher(C::zero_point(), C::zero_point()) }
+
+    /// Combine all the ciphers, cf. [combine](Self::combine). The sum of no
+    /// ciphers is [zero](Self::zero).
+    pub fn sum<'a, I: Iterator<Item = &'a Cipher<C>>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, c| acc.combine(c))
+    }
+
+    /// Same as [sum](Self::sum), but the ciphers are combined in parallel.
+    pub fn par_sum(ciphers: &[Cipher<C>]) -> Self {
+        ciphers
+            .par_iter()
+            .fold(Self::zero, |acc, c| acc.combine(c))
+            .reduce(Self::zero, |a, b| a.combine(&b))
+    }
+
     /// Scale the ciphertext by the given scalar. If the input is encryption of
     /// `m`, then the result is the encryption of `m^e`, where `e` is the given
     /// exponent.
@@ -90,6 +136,23 @@ impl<C: Curve> Cipher<C> {
 
     /// Same as `scale`, but provided for convenience.
     pub fn scale_u64(&self, e: u64) -> Self { self.scale(&C::scalar_from_u64(e)) }
+
+    /// Adjust the cipher, encrypted under some key `sk`, so that it decrypts
+    /// to the same message under the rotated key `sk + delta`, cf.
+    /// [SecretKey::rotate](crate::SecretKey::rotate). The randomness is
+    /// unchanged, so the result is linkable to the original cipher. NB: delta
+    /// must be kept as secret as the keys.
+    pub fn reencrypt_for_rotation(&self, delta: &C::Scalar) -> Self {
+        Self(self.0, self.1.plus_point(&self.0.mul_by_scalar(delta)))
+    }
+
+    /// Compare two ciphers in constant time by comparing their canonical
+    /// serializations. This should be preferred over `==` when one of the
+    /// ciphers is derived from secret data, since `==` may exit early and
+    /// thus leak information about where the ciphers differ.
+    pub fn ct_eq(&self, other: &Cipher<C>) -> Choice {
+        to_bytes(self).as_slice().ct_eq(to_bytes(other).as_slice())
+    }
 }
 
 /// Perform a "linear combination in the exponent", i.e., multiply each of the
@@ -112,8 +175,42 @@ pub fn multicombine<C: Curve>(ciphers: &[Cipher<C>], scalars: &[C::Scalar]) -> C
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{BabyStepGiantStep, PublicKey, SecretKey};
     use pairing::bls12_381::{G1, G2};
 
+    // Test that the sum of encryptions in the exponent decrypts to the sum of
+    // the values, and that summing in parallel gives the same cipher.
+    #[test]
+    fn cipher_sum() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        let values = (0..200u64).map(|i| i * 7 % 100).collect::<Vec<_>>();
+        let ciphers = values
+            .iter()
+            .map(|v| pk.encrypt_exponent(&mut csprng, &Value::from(*v)))
+            .collect::<Vec<_>>();
+        let total = Cipher::sum(ciphers.iter());
+        assert_eq!(Cipher::par_sum(&ciphers), total);
+        let bsgs = BabyStepGiantStep::new(&sk.generator, 1 << 8);
+        assert_eq!(
+            sk.decrypt_exponent(&total, &bsgs),
+            values.iter().sum::<u64>()
+        );
+        let zero = Cipher::<G1>::zero();
+        assert_eq!(Cipher::sum(std::iter::empty()), zero);
+        assert_eq!(Cipher::par_sum(&[]), zero);
+        assert_eq!(sk.decrypt_exponent(&zero, &bsgs), 0);
+        assert!(sk.decrypt(&zero).value.is_zero_point());
+    }
+
+    #[test]
+    fn cipher_serialization_roundtrip() {
+        let mut csprng = thread_rng();
+        assert_serialization_roundtrip(&mut csprng, 20, Cipher::<G1>::generate);
+        assert_serialization_roundtrip(&mut csprng, 20, Cipher::<G2>::generate);
+    }
+
     macro_rules! macro_test_cipher_to_byte_conversion {
         ($function_name:ident, $curve_type:path) => {
             #[test]
@@ -131,4 +228,86 @@ mod tests {
 
     macro_test_cipher_to_byte_conversion!(key_to_cipher_conversion_g1, G1);
     macro_test_cipher_to_byte_conversion!(key_to_cipher_conversion_g2, G2);
+
+    #[test]
+    pub fn cipher_serialization_is_tuple() {
+        let mut csprng = thread_rng();
+        let c: Cipher<G2> = Cipher::generate(&mut csprng);
+        let bytes = to_bytes(&c);
+        assert_eq!(bytes, to_bytes(&(c.0, c.1)));
+        let (a, b): (G2, G2) =
+            from_bytes(&mut std::io::Cursor::new(&bytes)).expect("Valid pair of points.");
+        assert_eq!(Cipher(a, b), c);
+    }
+
+    #[test]
+    pub fn cipher_ct_eq_agrees_with_eq() {
+        let mut csprng = thread_rng();
+        for _i in 1..100 {
+            let c: Cipher<G1> = Cipher::generate(&mut csprng);
+            let d: Cipher<G1> = Cipher::generate(&mut csprng);
+            let half = Cipher(c.0, d.1);
+            let e = serialize_deserialize(&c).expect("Serialization should succeed.");
+            assert!(bool::from(c.ct_eq(&e)));
+            assert_eq!(bool::from(c.ct_eq(&d)), c == d);
+            assert_eq!(bool::from(c.ct_eq(&half)), c == half);
+        }
+    }
+
+    #[test]
+    pub fn cipher_from_bytes_errors() {
+        let mut csprng = thread_rng();
+        for _i in 1..20 {
+            let c: Cipher<G1> = Cipher::generate(&mut csprng);
+            let bytes = to_bytes(&c);
+            assert_eq!(Cipher::<G1>::from_bytes(&bytes).expect("Valid cipher."), c);
+            match Cipher::<G1>::from_bytes(&bytes[..95]) {
+                Err(ElgamalError(InternalError::WrongLength { expected, got })) => {
+                    assert_eq!((expected, got), (96, 95))
+                }
+                r => panic!("Expected a length error, got {:?}.", r),
+            }
+            let mut bad = bytes.clone();
+            bad[48..].copy_from_slice(&[0u8; 48]);
+            match Cipher::<G1>::from_bytes(&bad) {
+                Err(ElgamalError(InternalError::PointDecode { component })) => {
+                    assert_eq!(component, "second component of the cipher")
+                }
+                r => panic!("Expected a decoding error, got {:?}.", r),
+            }
+            let m = crate::Message::<G1>::from_bytes(&bytes[..48]).expect("Valid message.");
+            assert_eq!(m.value, c.0);
+        }
+    }
+
+    #[test]
+    pub fn cipher_from_bytes_many() {
+        let mut csprng = thread_rng();
+        let ciphers = (0..5)
+            .map(|_| Cipher::<G1>::generate(&mut csprng))
+            .collect::<Vec<_>>();
+        let bytes = ciphers.iter().flat_map(to_bytes).collect::<Vec<_>>();
+        assert_eq!(
+            Cipher::<G1>::from_bytes_many(&bytes).expect("Valid ciphers."),
+            ciphers
+        );
+        assert_eq!(
+            Cipher::<G1>::from_bytes_many(&[]).expect("No ciphers."),
+            Vec::new()
+        );
+        let mut bad = bytes.clone();
+        bad[2 * 96..2 * 96 + 48].copy_from_slice(&[0u8; 48]);
+        match Cipher::<G1>::from_bytes_many(&bad) {
+            Err((2, ElgamalError(InternalError::PointDecode { component }))) => {
+                assert_eq!(component, "first component of the cipher")
+            }
+            r => panic!("Expected a decoding error at index 2, got {:?}.", r),
+        }
+        match Cipher::<G1>::from_bytes_many(&bytes[..4 * 96 + 10]) {
+            Err((4, ElgamalError(InternalError::WrongLength { expected, got }))) => {
+                assert_eq!((expected, got), (96, 10))
+            }
+            r => panic!("Expected a length error at index 4, got {:?}.", r),
+        }
+    }
 }
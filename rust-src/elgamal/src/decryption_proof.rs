@@ -0,0 +1,123 @@
+//! Proofs of correct decryption.
+
+use crate::{cipher::*, message::*, public::*, secret::*};
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::Curve;
+use ff::Field;
+use rand::*;
+use random_oracle::Transcript;
+
+/// Domain separation of the challenge of a [DecryptionProof].
+const DECRYPTION_PROOF_DOMAIN: &[u8] = b"ElgamalDecryptionProof";
+
+/// A Chaum-Pedersen proof that a message is the decryption of a cipher under
+/// the secret key corresponding to a public key `(g, h)`. For a cipher
+/// `(c_0, c_1)` and message `m` it proves knowledge of `x` such that `h = x g`
+/// and `c_1 - m = x c_0`, without revealing `x`. The proof is made
+/// non-interactive with the Fiat-Shamir transform.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct DecryptionProof<C: Curve> {
+    /// The challenge.
+    challenge: C::Scalar,
+    /// The response to the challenge.
+    response:  C::Scalar,
+}
+
+/// The challenge for the given statement and commitments.
+fn decryption_challenge<C: Curve>(
+    pk: &PublicKey<C>,
+    c: &Cipher<C>,
+    m: &Message<C>,
+    commitments: (&C, &C),
+) -> C::Scalar {
+    let mut transcript = Transcript::new(DECRYPTION_PROOF_DOMAIN);
+    transcript.append_serial("public_key", pk);
+    transcript.append_serial("cipher", c);
+    transcript.append_serial("message", m);
+    transcript.append_serial("commitment_generator", commitments.0);
+    transcript.append_serial("commitment_cipher", commitments.1);
+    transcript.challenge_scalar::<C, _>("challenge")
+}
+
+impl<C: Curve> SecretKey<C> {
+    /// Decrypt the cipher, and prove that the result is the decryption under
+    /// the public key corresponding to this secret key, cf.
+    /// [PublicKey::verify_decryption].
+    pub fn decrypt_with_proof<T: Rng>(
+        &self,
+        c: &Cipher<C>,
+        csprng: &mut T,
+    ) -> (Message<C>, DecryptionProof<C>) {
+        let m = self.decrypt(c);
+        let pk = PublicKey::from(self);
+        let k = C::generate_non_zero_scalar(csprng);
+        let a = self.generator.mul_by_scalar(&k);
+        let b = c.0.mul_by_scalar(&k);
+        let challenge = decryption_challenge(&pk, c, &m, (&a, &b));
+        // response = k - challenge * x
+        let mut response = challenge;
+        response.mul_assign(&self.scalar);
+        response.negate();
+        response.add_assign(&k);
+        (m, DecryptionProof {
+            challenge,
+            response,
+        })
+    }
+}
+
+impl<C: Curve> PublicKey<C> {
+    /// Check a proof that the message is the decryption of the cipher under
+    /// the secret key corresponding to this public key, as produced by
+    /// [SecretKey::decrypt_with_proof].
+    pub fn verify_decryption(
+        &self,
+        c: &Cipher<C>,
+        m: &Message<C>,
+        proof: &DecryptionProof<C>,
+    ) -> bool {
+        // Recompute the commitments a = response * g + challenge * h and
+        // b = response * c_0 + challenge * (c_1 - m).
+        let a = self
+            .generator
+            .mul_by_scalar(&proof.response)
+            .plus_point(&self.key.mul_by_scalar(&proof.challenge));
+        let b =
+            c.0.mul_by_scalar(&proof.response)
+                .plus_point(&c.1.minus_point(&m.value).mul_by_scalar(&proof.challenge));
+        decryption_challenge(self, c, m, (&a, &b)) == proof.challenge
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::{G1, G2};
+
+    fn test_decryption_proof_generic<C: Curve>() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<C>::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        for _ in 0..10 {
+            let m = Message::generate(&mut csprng);
+            let c = pk.encrypt(&mut csprng, &m);
+            let (decrypted, proof) = sk.decrypt_with_proof(&c, &mut csprng);
+            assert_eq!(decrypted, m);
+            assert!(pk.verify_decryption(&c, &m, &proof));
+            assert_eq!(serialize_deserialize(&proof).ok(), Some(proof));
+            let wrong = Message::generate(&mut csprng);
+            assert!(!pk.verify_decryption(&c, &wrong, &proof), "Wrong message.");
+            let other = pk.encrypt(&mut csprng, &m);
+            assert!(!pk.verify_decryption(&other, &m, &proof), "Wrong cipher.");
+            let other_pk = PublicKey::from(&SecretKey::generate(&sk.generator, &mut csprng));
+            assert!(!other_pk.verify_decryption(&c, &m, &proof), "Wrong key.");
+        }
+    }
+
+    #[test]
+    fn decryption_proof_g1() { test_decryption_proof_generic::<G1>() }
+
+    #[test]
+    fn decryption_proof_g2() { test_decryption_proof_generic::<G2>() }
+}
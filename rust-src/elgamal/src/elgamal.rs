@@ -148,6 +148,11 @@ pub fn encrypt_in_chunks_given_generator<C: Curve, R: Rng>(
 
 /// Encrypt a single `u64` value in chunks in the exponent of the given
 /// generator.
+///
+/// The chunks are in little-endian order, i.e., the cipher at index 0
+/// encrypts the least significant chunk. In particular, with
+/// [ChunkSize::One] the cipher at index `i` encrypts bit `i` of the value,
+/// where bit 0 is the least significant bit.
 pub fn encrypt_u64_in_chunks_given_generator<C: Curve, R: Rng>(
     pk: &PublicKey<C>,
     val: u64,
@@ -163,6 +168,59 @@ pub fn encrypt_u64_in_chunks_given_generator<C: Curve, R: Rng>(
     pk.encrypt_exponent_vec_given_generator(&chunks, generator, csprng)
 }
 
+/// Encrypt only the significant bits of a `u64` value, bit by bit, in the
+/// exponent of the generator of the public key. The first component of the
+/// result is the number of significant bits, i.e., the position of the
+/// highest set bit plus one, and 0 for the value 0. The ciphers are in
+/// little-endian order as in [encrypt_u64_in_chunks_given_generator], and
+/// there is one cipher per significant bit.
+///
+/// NB: The number of ciphers, and the returned length, are not hidden, so this
+/// leaks the magnitude of the value (its base-2 logarithm). Use
+/// [encrypt_u64_in_chunks_given_generator] if that is not acceptable.
+pub fn encrypt_u64_sparse<C: Curve, R: Rng>(
+    pk: &PublicKey<C>,
+    val: u64,
+    csprng: &mut R,
+) -> (u8, Vec<Cipher<C>>) {
+    let length = (64 - val.leading_zeros()) as u8;
+    let bits = ChunkSize::One
+        .u64_to_chunks(val)
+        .into_iter()
+        .take(usize::from(length))
+        .map(Value::from)
+        .collect::<Vec<_>>();
+    let ciphers = pk
+        .encrypt_exponent_vec_given_generator(&bits, &pk.generator, csprng)
+        .into_iter()
+        .map(|(c, _)| c)
+        .collect();
+    (length, ciphers)
+}
+
+/// Decrypt a value encrypted with [encrypt_u64_sparse]. This returns `None` if
+/// the number of ciphers does not match the length, if the length is more
+/// than 64, or if some cipher does not encrypt a bit.
+pub fn decrypt_u64_sparse<C: Curve>(
+    sk: &SecretKey<C>,
+    length: u8,
+    ciphers: &[Cipher<C>],
+) -> Option<u64> {
+    if length > 64 || ciphers.len() != usize::from(length) {
+        return None;
+    }
+    let mut val = 0u64;
+    for (i, cipher) in ciphers.iter().enumerate() {
+        let point = sk.decrypt(cipher).value;
+        if point == sk.generator {
+            val |= 1 << i;
+        } else if !point.is_zero_point() {
+            return None;
+        }
+    }
+    Some(val)
+}
+
 /// Wrapper around `decrypt_from_chunks_given_generator` that uses the generator
 /// that is part of the key.
 pub fn decrypt_from_chunks<C: Curve>(
@@ -313,4 +371,60 @@ mod tests {
 
     #[test]
     fn chunked_encrypt_decrypt_test_g1() { test_chunked_encrypt_decrypt_generic::<G1>() }
+
+    // Check that with chunk size one the cipher at index i encrypts bit i of the
+    // value, with bit 0 being the least significant one.
+    #[test]
+    fn encrypt_u64_bit_order() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate_all(&mut csprng);
+        let pk = PublicKey::<G1>::from(&sk);
+        let bsgs = BabyStepGiantStep::new(&sk.generator, 2);
+        for &(val, set_bit) in &[(1u64, 0usize), (0x8000_0000_0000_0000, 63)] {
+            let ciphers = encrypt_u64_in_chunks_given_generator(
+                &pk,
+                val,
+                ChunkSize::One,
+                &sk.generator,
+                &mut csprng,
+            );
+            assert_eq!(ciphers.len(), 64, "There should be one cipher per bit.");
+            for (i, (c, _)) in ciphers.iter().enumerate() {
+                let expected = if i == set_bit { 1 } else { 0 };
+                assert_eq!(
+                    sk.decrypt_exponent(c, &bsgs),
+                    expected,
+                    "Bit {} of {:#x}.",
+                    i,
+                    val
+                );
+            }
+        }
+    }
+
+    fn test_sparse_roundtrip_generic<C: Curve>() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<C>::generate_all(&mut csprng);
+        let pk = PublicKey::<C>::from(&sk);
+        for &(val, expected_length) in &[(0u64, 0u8), (5, 3), (u64::MAX, 64)] {
+            let (length, ciphers) = encrypt_u64_sparse(&pk, val, &mut csprng);
+            assert_eq!(length, expected_length, "Length of {}.", val);
+            assert_eq!(ciphers.len(), usize::from(length));
+            assert_eq!(decrypt_u64_sparse(&sk, length, &ciphers), Some(val));
+        }
+        let (length, ciphers) = encrypt_u64_sparse(&pk, 5, &mut csprng);
+        assert_eq!(
+            decrypt_u64_sparse(&sk, length + 1, &ciphers),
+            None,
+            "Length must match the number of ciphers."
+        );
+        let not_a_bit = pk.encrypt_exponent(&mut csprng, &Value::from(2u64));
+        assert_eq!(decrypt_u64_sparse(&sk, 1, &[not_a_bit]), None);
+    }
+
+    #[test]
+    fn sparse_roundtrip_g1() { test_sparse_roundtrip_generic::<G1>() }
+
+    #[test]
+    fn sparse_roundtrip_g2() { test_sparse_roundtrip_generic::<G2>() }
 }
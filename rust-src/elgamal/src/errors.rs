@@ -2,18 +2,20 @@
 // Display) should be snake cased, for some reason.
 #![allow(non_snake_case)]
 
+use crypto_common::from_bytes;
+use curve_arithmetic::Curve;
+use std::io::Cursor;
 use thiserror::Error;
 /// Internal errors.  
 
-#[derive(Error, Debug)]
-#[allow(dead_code)]
+#[derive(Error, Debug, PartialEq, Eq)]
 pub(crate) enum InternalError {
-    #[error("wrong publickey length")]
-    PublicKey,
-    #[error("wrong message length")]
-    Message,
-    #[error("wrong cipher length")]
-    Cipher,
+    #[error("wrong length of input: expected {expected} bytes, got {got}")]
+    WrongLength { expected: usize, got: usize },
+    #[error("could not decode the {component} as a group element")]
+    PointDecode { component: &'static str },
+    #[error("could not decode the {component} as a scalar")]
+    ScalarDecode { component: &'static str },
 }
 
 /// Errors which may occur while processing keys, encryption and decryptoin.
@@ -25,7 +27,28 @@ pub(crate) enum InternalError {
 /// * A problem decoding to a scalar,
 ///
 /// * A problem  decoding to a group element
-
 #[derive(Error, Debug)]
 #[error("{0}")]
 pub struct ElgamalError(pub(crate) InternalError);
+
+/// Check that the input is exactly of the expected length.
+pub(crate) fn check_length(bytes: &[u8], expected: usize) -> Result<(), ElgamalError> {
+    if bytes.len() == expected {
+        Ok(())
+    } else {
+        Err(ElgamalError(InternalError::WrongLength {
+            expected,
+            got: bytes.len(),
+        }))
+    }
+}
+
+/// Decode a group element from exactly the given bytes. The component is used
+/// in the error to indicate which part of the input failed to decode.
+pub(crate) fn decode_point<C: Curve>(
+    bytes: &[u8],
+    component: &'static str,
+) -> Result<C, ElgamalError> {
+    from_bytes(&mut Cursor::new(bytes))
+        .map_err(|_| ElgamalError(InternalError::PointDecode { component }))
+}
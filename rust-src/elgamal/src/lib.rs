@@ -1,13 +1,18 @@
 //! Implementation of elgamal public key encryption and decryption over a Curve.
 
 mod cipher;
+mod decryption_proof;
 mod elgamal;
 mod errors;
 mod message;
 mod public;
 mod secret;
+mod threshold;
 
-pub use crate::{cipher::*, elgamal::*, message::*, public::*, secret::*};
+pub use crate::{
+    cipher::*, decryption_proof::*, elgamal::*, errors::ElgamalError, message::*, public::*,
+    secret::*, threshold::*,
+};
 
 #[macro_use]
 extern crate crypto_common_derive;
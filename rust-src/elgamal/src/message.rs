@@ -2,9 +2,10 @@
 
 use rand::*;
 
+use crate::errors::*;
 use crypto_common::*;
 use crypto_common_derive::*;
-use curve_arithmetic::Curve;
+use curve_arithmetic::{Curve, Value};
 
 #[derive(Debug, PartialEq, Eq, Serialize, SerdeBase16Serialize)]
 #[repr(transparent)]
@@ -23,6 +24,38 @@ impl<C: Curve> Message<C> {
             value: C::generate(csprng),
         }
     }
+
+    /// Construct a message from exactly `C::GROUP_ELEMENT_LENGTH` bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Message<C>, ElgamalError> {
+        check_length(bytes, C::GROUP_ELEMENT_LENGTH)?;
+        Ok(Message {
+            value: decode_point(bytes, "message")?,
+        })
+    }
+
+    /// The message whose group element is `generator * e`. This is the form in
+    /// which values are encrypted in the exponent.
+    pub fn from_exponent(generator: &C, e: &Value<C>) -> Self {
+        Message {
+            value: generator.mul_by_scalar(e),
+        }
+    }
+
+    /// The group element of the message.
+    pub fn point(&self) -> &C { &self.value }
+
+    /// Blind the message with the given scalar. This returns the blinded
+    /// message `m + g * r`, where `g` is the generator of the group and `r` is
+    /// the blinding factor, together with the public image `g * r` of the
+    /// blinding factor. The original message is recovered by subtracting the
+    /// image from the blinded message.
+    pub fn blind(&self, blinding: &C::Scalar) -> (Message<C>, C) {
+        let image = C::one_point().mul_by_scalar(blinding);
+        let blinded = Message {
+            value: self.value.plus_point(&image),
+        };
+        (blinded, image)
+    }
 }
 
 #[cfg(test)]
@@ -40,6 +73,26 @@ mod tests {
         }
     }
 
+    fn test_message_blind_helper<C: Curve>() {
+        let mut csprng = thread_rng();
+        for _i in 1..100 {
+            let m: Message<C> = Message::generate(&mut csprng);
+            let r = C::generate_scalar(&mut csprng);
+            let (blinded, image) = m.blind(&r);
+            assert_ne!(blinded, m, "Blinding should change the message.");
+            assert_eq!(
+                blinded.value.minus_point(&image),
+                m.value,
+                "Unblinding should recover the message."
+            );
+        }
+    }
+
+    #[test]
+    pub fn message_blind_g1() { test_message_blind_helper::<G1>(); }
+    #[test]
+    pub fn message_blind_g2() { test_message_blind_helper::<G2>(); }
+
     #[test]
     pub fn message_to_byte_conversion_g1() { test_message_serialization_helper::<G1>(); }
     #[test]
@@ -3,11 +3,13 @@
 use core::fmt::Debug;
 use rand::*;
 
-use crate::{cipher::*, message::*, secret::*};
+use crate::{cipher::*, errors::*, message::*, secret::*};
 
 use crypto_common::*;
 use crypto_common_derive::*;
-use curve_arithmetic::{multiexp, Curve, Value};
+use curve_arithmetic::{multiexp, Curve, CurveDecodingError, Value};
+use ff::PrimeField;
+use rayon::iter::*;
 
 /// Elgamal public key .
 #[derive(Copy, Clone, Eq, PartialEq, Serialize, SerdeBase16Serialize)]
@@ -32,17 +34,60 @@ impl<C: Curve> From<&SecretKey<C>> for PublicKey<C> {
 }
 
 impl<C: Curve> PublicKey<C> {
+    /// Construct a public key from exactly `2 * C::GROUP_ELEMENT_LENGTH`
+    /// bytes, the generator followed by the key.
+    pub fn from_bytes(bytes: &[u8]) -> Result<PublicKey<C>, ElgamalError> {
+        check_length(bytes, 2 * C::GROUP_ELEMENT_LENGTH)?;
+        let (generator, key) = bytes.split_at(C::GROUP_ELEMENT_LENGTH);
+        Ok(PublicKey {
+            generator: decode_point(generator, "generator of the public key")?,
+            key:       decode_point(key, "public key")?,
+        })
+    }
+
+    /// Construct a public key from the generator and the key, e.g., when only
+    /// the public key is stored. Both points must be different from the
+    /// identity, since encryption with such a key would not hide the message.
+    /// Encryption uses the given generator.
+    pub fn from_parts(generator: C, key: C) -> Result<PublicKey<C>, CurveDecodingError> {
+        generator.require_non_identity()?;
+        key.require_non_identity()?;
+        Ok(PublicKey { generator, key })
+    }
+
+    /// The generator with respect to which this key was derived.
+    pub fn generator_point(&self) -> &C { &self.generator }
+
+    /// Precompute a table of multiples of the generator to speed up
+    /// encryption with this key. This is worthwhile when many messages are
+    /// encrypted with the same key.
+    pub fn with_precomputation(self) -> PrecomputedPublicKey<C> {
+        let num_windows = (C::Scalar::NUM_BITS as usize).div_ceil(FIXED_BASE_WINDOW);
+        let mut table = Vec::with_capacity(num_windows);
+        let mut base = self.generator;
+        for _ in 0..num_windows {
+            let mut row = Vec::with_capacity((1 << FIXED_BASE_WINDOW) - 1);
+            let mut multiple = base;
+            for _ in 1..(1 << FIXED_BASE_WINDOW) {
+                row.push(multiple);
+                multiple = multiple.plus_point(&base);
+            }
+            table.push(row);
+            base = multiple;
+        }
+        PrecomputedPublicKey {
+            public_key: self,
+            table,
+        }
+    }
+
     /// Encrypt and returned the randomness used. NB: Randomness must be kept
     /// private.
     pub fn encrypt_rand<T>(&self, csprng: &mut T, m: &Message<C>) -> (Cipher<C>, Randomness<C>)
     where
         T: Rng, {
         let k = Randomness::generate(csprng);
-        let g = self.generator.mul_by_scalar(&k.randomness);
-        // FIXME: Could use multiexponentiation when we are calling from
-        // encrypt_exponent_rand.
-        let s = self.key.mul_by_scalar(&k.randomness).plus_point(&m.value);
-        (Cipher(g, s), k)
+        (self.hide(&k.randomness, m), k)
     }
 
     #[inline]
@@ -53,6 +98,22 @@ impl<C: Curve> PublicKey<C> {
         self.encrypt_rand(csprng, m).0
     }
 
+    /// Encrypt a sequence of messages, returning the ciphertexts in the same
+    /// order as the input. The randomness is sampled sequentially from the
+    /// given `csprng`, and the encryptions are then computed in parallel.
+    pub fn encrypt_iter<'a, T, I>(&self, iter: I, csprng: &mut T) -> Vec<Cipher<C>>
+    where
+        T: Rng,
+        I: Iterator<Item = &'a Message<C>>, {
+        let inputs = iter
+            .map(|m| (m, C::generate_non_zero_scalar(csprng)))
+            .collect::<Vec<_>>();
+        inputs.par_iter().map(|(m, k)| self.hide(k, m)).collect()
+    }
+
+    /// Encrypt the message with the given randomness. This is deterministic,
+    /// and is what the other encryption functions use after sampling the
+    /// randomness, so it can be used to produce reproducible test vectors.
     pub fn hide(&self, k: &C::Scalar, message: &Message<C>) -> Cipher<C> {
         let t = self.generator.mul_by_scalar(k);
         let s = self.key.mul_by_scalar(k).plus_point(&message.value);
@@ -84,7 +145,7 @@ impl<C: Curve> PublicKey<C> {
     ) -> (Cipher<C>, Randomness<C>)
     where
         T: Rng, {
-        let randomness = C::generate_scalar(csprng);
+        let randomness = C::generate_non_zero_scalar(csprng);
         let g = self.generator.mul_by_scalar(&randomness);
         let s = multiexp(&[self.key, *h], &[randomness, *e.as_ref()]);
         let randomness = Randomness::new(randomness);
@@ -145,9 +206,76 @@ impl<C: Curve> PublicKey<C> {
     }
 }
 
+/// Number of bits of the scalar handled by each row of the table of a
+/// [PrecomputedPublicKey].
+const FIXED_BASE_WINDOW: usize = 4;
+
+/// A public key together with a table of multiples of its generator, which
+/// makes encryption faster since the multiplication of the randomness with the
+/// generator only needs additions. The multiplication with the key itself is
+/// unchanged. Encryption gives the same ciphertexts as with the plain
+/// [PublicKey] for the same randomness.
+#[derive(Clone)]
+pub struct PrecomputedPublicKey<C: Curve> {
+    public_key: PublicKey<C>,
+    /// The `j`-th entry of row `i` is `(j + 1) * 2^(FIXED_BASE_WINDOW * i)`
+    /// times the generator.
+    table:      Vec<Vec<C>>,
+}
+
+impl<C: Curve> PrecomputedPublicKey<C> {
+    /// The public key the table was computed for.
+    pub fn public_key(&self) -> &PublicKey<C> { &self.public_key }
+
+    /// Multiply the generator by the scalar using the table. This is not
+    /// constant time.
+    fn mul_generator(&self, k: &C::Scalar) -> C {
+        let repr = k.into_repr();
+        let limbs = repr.as_ref();
+        let mask = (1u64 << FIXED_BASE_WINDOW) - 1;
+        let mut result = C::zero_point();
+        for (i, row) in self.table.iter().enumerate() {
+            let bit = i * FIXED_BASE_WINDOW;
+            let digit = (limbs[bit / 64] >> (bit % 64)) & mask;
+            if digit != 0 {
+                result = result.plus_point(&row[digit as usize - 1]);
+            }
+        }
+        result
+    }
+
+    /// Encrypt using the given randomness, cf. [PublicKey::hide].
+    pub fn hide(&self, k: &C::Scalar, message: &Message<C>) -> Cipher<C> {
+        let t = self.mul_generator(k);
+        let s = self
+            .public_key
+            .key
+            .mul_by_scalar(k)
+            .plus_point(&message.value);
+        Cipher(t, s)
+    }
+
+    /// Encrypt and return the randomness used, cf. [PublicKey::encrypt_rand].
+    /// NB: Randomness must be kept private.
+    pub fn encrypt_rand<T>(&self, csprng: &mut T, m: &Message<C>) -> (Cipher<C>, Randomness<C>)
+    where
+        T: Rng, {
+        let k = Randomness::generate(csprng);
+        (self.hide(&k.randomness, m), k)
+    }
+
+    /// Wrapper around `encrypt_rand` that forgets the randomness.
+    pub fn encrypt<T>(&self, csprng: &mut T, m: &Message<C>) -> Cipher<C>
+    where
+        T: Rng, {
+        self.encrypt_rand(csprng, m).0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ff::Field;
     use pairing::bls12_381::{G1, G2};
 
     macro_rules! macro_test_key_to_byte_conversion {
@@ -169,4 +297,124 @@ mod tests {
 
     macro_test_key_to_byte_conversion!(key_to_byte_conversion_g1, G1);
     macro_test_key_to_byte_conversion!(key_to_byte_conversion_g2, G2);
+
+    // Test that generators with the same seed give the same keys and ciphers.
+    #[test]
+    fn test_seeded_rng_deterministic() {
+        let run = |seed: &[u8; 32]| {
+            let mut csprng = crypto_common::rng::seeded_rng(seed);
+            let sk: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+            let pk = PublicKey::from(&sk);
+            let cs = (0..5)
+                .map(|_| {
+                    let m = Message::generate(&mut csprng);
+                    pk.encrypt(&mut csprng, &m)
+                })
+                .collect::<Vec<_>>();
+            (sk, cs)
+        };
+        let (sk1, cs1) = run(&[7u8; 32]);
+        let (sk2, cs2) = run(&[7u8; 32]);
+        assert_eq!(sk1, sk2, "Same seed should give the same key.");
+        assert_eq!(cs1, cs2, "Same seed should give the same ciphers.");
+        let (sk3, _) = run(&[8u8; 32]);
+        assert_ne!(sk1, sk3, "Different seeds should give different keys.");
+    }
+
+    // Test that encrypting and then decrypting a sequence of messages gives back
+    // the original messages in the original order.
+    fn test_encrypt_decrypt_iter_generic<C: Curve>() {
+        let mut csprng = thread_rng();
+        let sk: SecretKey<C> = SecretKey::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        for n in 0..20 {
+            let ms = (0..n)
+                .map(|_| Message::generate(&mut csprng))
+                .collect::<Vec<Message<C>>>();
+            let cs = pk.encrypt_iter(ms.iter(), &mut csprng);
+            assert_eq!(cs.len(), n, "Number of ciphertexts should match input.");
+            let ms2 = sk.decrypt_iter(cs.iter());
+            assert_eq!(ms, ms2, "Decrypted messages differ from the originals.");
+        }
+    }
+
+    #[test]
+    fn encrypt_decrypt_iter_g1() { test_encrypt_decrypt_iter_generic::<G1>() }
+
+    #[test]
+    fn encrypt_decrypt_iter_g2() { test_encrypt_decrypt_iter_generic::<G2>() }
+
+    // Test that encryption with given randomness is deterministic and agrees
+    // with encryption with sampled randomness.
+    fn test_hide_generic<C: Curve>() {
+        let mut csprng = thread_rng();
+        let sk: SecretKey<C> = SecretKey::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        let m = Message::generate(&mut csprng);
+        let k = C::generate_scalar(&mut csprng);
+        let c = pk.hide(&k, &m);
+        assert_eq!(c, pk.hide(&k, &m), "Same randomness, same cipher.");
+        assert_eq!(sk.decrypt(&c), m);
+        let (c, r) = pk.encrypt_rand(&mut csprng, &m);
+        assert_eq!(c, pk.hide(&r.randomness, &m));
+    }
+
+    #[test]
+    fn hide_g1() { test_hide_generic::<G1>() }
+
+    #[test]
+    fn hide_g2() { test_hide_generic::<G2>() }
+
+    // Test that a key constructed from its parts encrypts for the matching
+    // secret key, and that the identity is rejected in either part.
+    fn test_from_parts_generic<C: Curve>() {
+        let mut csprng = thread_rng();
+        let sk: SecretKey<C> = SecretKey::generate_all(&mut csprng);
+        let derived = PublicKey::from(&sk);
+        let pk = PublicKey::from_parts(sk.generator, derived.key).expect("Valid parts.");
+        assert_eq!(pk, derived);
+        for _ in 0..10 {
+            let m = Message::generate(&mut csprng);
+            assert_eq!(sk.decrypt(&pk.encrypt(&mut csprng, &m)), m);
+        }
+        assert!(PublicKey::from_parts(C::zero_point(), derived.key).is_err());
+        assert!(PublicKey::from_parts(sk.generator, C::zero_point()).is_err());
+    }
+
+    #[test]
+    fn from_parts_g1() { test_from_parts_generic::<G1>() }
+
+    #[test]
+    fn from_parts_g2() { test_from_parts_generic::<G2>() }
+
+    // Test that the precomputed key encrypts exactly like the plain key.
+    fn test_precomputed_encrypt_generic<C: Curve>() {
+        let mut csprng = thread_rng();
+        let sk: SecretKey<C> = SecretKey::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        let precomputed = pk.with_precomputation();
+        assert_eq!(precomputed.public_key(), &pk);
+        let edge_cases = [C::Scalar::zero(), C::Scalar::one(), {
+            let mut minus_one = C::Scalar::zero();
+            minus_one.sub_assign(&C::Scalar::one());
+            minus_one
+        }];
+        for k in edge_cases.iter() {
+            let m = Message::generate(&mut csprng);
+            assert_eq!(precomputed.hide(k, &m), pk.hide(k, &m));
+        }
+        for _ in 0..20 {
+            let m = Message::generate(&mut csprng);
+            let k = C::generate_scalar(&mut csprng);
+            assert_eq!(precomputed.hide(&k, &m), pk.hide(&k, &m));
+            let c = precomputed.encrypt(&mut csprng, &m);
+            assert_eq!(sk.decrypt(&c), m);
+        }
+    }
+
+    #[test]
+    fn precomputed_encrypt_g1() { test_precomputed_encrypt_generic::<G1>() }
+
+    #[test]
+    fn precomputed_encrypt_g2() { test_precomputed_encrypt_generic::<G2>() }
 }
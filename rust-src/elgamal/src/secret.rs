@@ -1,13 +1,15 @@
 // -*- mode: rust; -*-
 
 //! Elgamal secret key types
-use crate::{cipher::*, message::*};
+use crate::{cipher::*, errors::*, message::*, public::PublicKey};
 use anyhow::{bail, Result};
 use crypto_common::*;
-use curve_arithmetic::{Curve, Value};
+use curve_arithmetic::{Curve, CurveDecodingError, Value};
 use ff::Field;
 use rand::*;
+use rayon::iter::*;
 use std::collections::HashMap;
+use subtle::{ConditionallySelectable, ConstantTimeEq};
 
 /// Elgamal secret key packed together with a chosen generator.
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, SerdeBase16Serialize)]
@@ -89,6 +91,19 @@ impl<C: Curve> BabyStepGiantStep<C> {
         }
     }
 
+    /// Generate a new instance that can compute discrete logarithms of values
+    /// up to and including `max` with at most `m` giant steps, where `m` is
+    /// the size of the table. The table size is chosen to be roughly
+    /// `sqrt(max)`, which minimizes the cost of a single lookup. When many
+    /// values are decrypted with the same table a larger table may be better.
+    pub fn for_max(base: &C, max: u64) -> Self {
+        let mut m = (max as f64).sqrt() as u64;
+        while m.saturating_mul(m) <= max {
+            m += 1;
+        }
+        Self::new(base, m)
+    }
+
     /// Compute the discrete log using the instance. This function's performance
     /// is linear in `l / m` where `l` is the value stored in the exponent of
     /// `v`, and `m` is the size of the table.
@@ -115,6 +130,23 @@ impl<C: Curve> BabyStepGiantStep<C> {
 }
 
 impl<C: Curve> SecretKey<C> {
+    /// Construct a secret key from exactly `C::GROUP_ELEMENT_LENGTH +
+    /// C::SCALAR_LENGTH` bytes, the generator followed by the scalar.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SecretKey<C>, ElgamalError> {
+        check_length(bytes, C::GROUP_ELEMENT_LENGTH + C::SCALAR_LENGTH)?;
+        let (generator, scalar) = bytes.split_at(C::GROUP_ELEMENT_LENGTH);
+        let component = "secret key";
+        Ok(SecretKey {
+            generator: decode_point(generator, "generator of the secret key")?,
+            scalar:    from_bytes(&mut std::io::Cursor::new(scalar))
+                .map_err(|_| ElgamalError(InternalError::ScalarDecode { component }))?,
+        })
+    }
+
+    /// Decrypt the cipher to the group element that was encrypted. This does
+    /// not attempt to recover the exponent of a value encrypted with
+    /// [PublicKey::encrypt_exponent](crate::PublicKey::encrypt_exponent), for
+    /// which see [SecretKey::decrypt_exponent].
     pub fn decrypt(&self, c: &Cipher<C>) -> Message<C> {
         let x = c.0; // k * g
         let kag = x.mul_by_scalar(&self.scalar); // k * a * g
@@ -123,6 +155,20 @@ impl<C: Curve> SecretKey<C> {
         Message { value }
     }
 
+    /// Same as [SecretKey::decrypt]. This is the clearer name when the result
+    /// is used as a group element, e.g., in further homomorphic operations,
+    /// rather than decrypted in the exponent.
+    pub fn decrypt_message(&self, c: &Cipher<C>) -> Message<C> { self.decrypt(c) }
+
+    /// Decrypt a sequence of ciphertexts in parallel, returning the messages
+    /// in the same order as the input.
+    pub fn decrypt_iter<'a, I>(&self, iter: I) -> Vec<Message<C>>
+    where
+        I: Iterator<Item = &'a Cipher<C>>, {
+        let ciphers = iter.collect::<Vec<_>>();
+        ciphers.par_iter().map(|c| self.decrypt(c)).collect()
+    }
+
     pub fn decrypt_exponent_slow(&self, c: &Cipher<C>) -> Value<C> {
         let m = self.decrypt(c).value;
         let mut a = <C::Scalar as Field>::zero();
@@ -146,20 +192,85 @@ impl<C: Curve> SecretKey<C> {
         bsgs.discrete_log(&dec)
     }
 
-    /// Generate a `SecretKey` from a `csprng`.
+    /// Decrypt a value in the exponent that is at most `max`, or return `None`
+    /// if the value is larger. Unlike [SecretKey::decrypt_exponent] this
+    /// always computes all of `0, 1, ..., max` times the generator and compares
+    /// each of them to the decrypted point, selecting the match without
+    /// branching, so that the running time depends only on `max` and not on
+    /// the encrypted value.
+    ///
+    /// This is at the cost of always taking the worst case time, which is
+    /// linear in `max`, compared to the square root of the value for baby step
+    /// giant step. It is thus only practical for small values, e.g., chunks of
+    /// an amount. Note that the timing independence is only as good as that of
+    /// the underlying group operations.
+    pub fn decrypt_exponent_ct(&self, c: &Cipher<C>, max: u64) -> Option<u64> {
+        let target = to_bytes(&self.decrypt(c).value);
+        let mut point = C::zero_point();
+        let mut found = subtle::Choice::from(0);
+        let mut result = 0u64;
+        for i in 0..=max {
+            let matches = to_bytes(&point).as_slice().ct_eq(target.as_slice());
+            result.conditional_assign(&i, matches);
+            found |= matches;
+            point = point.plus_point(&self.generator);
+        }
+        if bool::from(found) {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    /// Check whether the given public key corresponds to this secret key, i.e.,
+    /// whether it has the same generator and its key is the generator
+    /// multiplied by the secret scalar. The comparison is in constant time.
+    pub fn matches_public(&self, pk: &PublicKey<C>) -> bool {
+        let expected = PublicKey::from(self);
+        ct::ct_eq_bytes(&to_bytes(&expected), &to_bytes(pk))
+    }
+
+    /// Rotate the key by adding `delta` to the secret scalar. Return the new
+    /// secret key and `delta` times the generator, which can be added to the
+    /// old public key to obtain the new one. Existing ciphers can be adjusted
+    /// to the new key with [Cipher::reencrypt_for_rotation] without decrypting
+    /// them.
+    pub fn rotate(&self, delta: &C::Scalar) -> (SecretKey<C>, C) {
+        let mut scalar = self.scalar;
+        scalar.add_assign(delta);
+        let rotated = SecretKey {
+            generator: self.generator,
+            scalar,
+        };
+        (rotated, self.generator.mul_by_scalar(delta))
+    }
+
+    /// Generate a `SecretKey` from a `csprng`. The secret scalar is never
+    /// zero.
     pub fn generate<T: Rng>(generator: &C, csprng: &mut T) -> Self {
         SecretKey {
             generator: *generator,
-            scalar:    C::generate_scalar(csprng),
+            scalar:    C::generate_non_zero_scalar(csprng),
         }
     }
 
+    /// Generate a `SecretKey` for the given generator, checking that the
+    /// generator is not the identity point. Encryption with the identity as
+    /// the generator would not hide the message.
+    pub fn with_generator<T: Rng>(
+        generator: &C,
+        csprng: &mut T,
+    ) -> Result<Self, CurveDecodingError> {
+        generator.require_non_identity()?;
+        Ok(Self::generate(generator, csprng))
+    }
+
     /// Generate a `SecretKey` as well as a generator.
     pub fn generate_all<T: Rng>(csprng: &mut T) -> Self {
         let x = C::generate_non_zero_scalar(csprng);
         SecretKey {
             generator: C::one_point().mul_by_scalar(&x),
-            scalar:    C::generate_scalar(csprng),
+            scalar:    C::generate_non_zero_scalar(csprng),
         }
     }
 }
@@ -187,6 +298,145 @@ mod tests {
     macro_test_secret_key_to_byte_conversion!(secret_key_to_byte_conversion_g1, G1);
     macro_test_secret_key_to_byte_conversion!(secret_key_to_byte_conversion_g2, G2);
 
+    #[test]
+    fn test_with_generator_rejects_identity() {
+        let mut csprng = thread_rng();
+        let res = SecretKey::with_generator(&G1::zero_point(), &mut csprng);
+        assert!(matches!(res, Err(CurveDecodingError::IdentityPoint)));
+        let g = G1::generate(&mut csprng);
+        let sk =
+            SecretKey::with_generator(&g, &mut csprng).expect("Generator is not the identity.");
+        assert_eq!(sk.generator, g);
+    }
+
+    // Test that ciphers adjusted for a rotation decrypt under the rotated key.
+    #[test]
+    fn test_rotate() {
+        let mut csprng = thread_rng();
+        let sk: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        let delta = G1::generate_scalar(&mut csprng);
+        let (rotated, public_delta) = sk.rotate(&delta);
+        let rotated_pk = PublicKey::from(&rotated);
+        assert_eq!(rotated_pk.key, pk.key.plus_point(&public_delta));
+        for _ in 0..10 {
+            let m = Message::generate(&mut csprng);
+            let c = pk.encrypt(&mut csprng, &m);
+            let adjusted = c.reencrypt_for_rotation(&delta);
+            assert_eq!(rotated.decrypt(&adjusted), m);
+            assert_ne!(rotated.decrypt(&c), m, "The cipher must be adjusted.");
+        }
+    }
+
+    // Test that the constant time decryption recovers small values and rejects
+    // values above the bound. Its running time is linear in the bound for all
+    // values, which is not measured here since timing tests are unreliable.
+    #[test]
+    fn test_decrypt_exponent_ct() {
+        let mut csprng = thread_rng();
+        let sk: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        let max = 100;
+        for &v in [0u64, 1, 37, max].iter() {
+            let c = pk.encrypt_exponent(&mut csprng, &Value::from(v));
+            assert_eq!(sk.decrypt_exponent_ct(&c, max), Some(v), "Value {}.", v);
+        }
+        let c = pk.encrypt_exponent(&mut csprng, &Value::from(max + 1));
+        assert_eq!(sk.decrypt_exponent_ct(&c, max), None, "Above the bound.");
+    }
+
+    // Test that decryption of a batch with a shared table agrees with the slow
+    // decryption.
+    #[test]
+    fn test_decrypt_exponent_shared_table() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate_all(&mut csprng);
+        let pk = crate::public::PublicKey::from(&sk);
+        let max = 1000;
+        let bsgs = BabyStepGiantStep::for_max(&sk.generator, max);
+        for v in [0, 1, 31, 32, 33, 999, 1000].iter() {
+            let c = pk.encrypt_exponent(&mut csprng, &Value::from(*v));
+            let slow = sk.decrypt_exponent_slow(&c);
+            assert_eq!(slow, Value::from(*v));
+            assert_eq!(
+                sk.decrypt_exponent(&c, &bsgs),
+                *v,
+                "Shared table for {}.",
+                v
+            );
+        }
+    }
+
+    #[test]
+    fn test_keys_from_bytes() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate_all(&mut csprng);
+        let pk = crate::public::PublicKey::from(&sk);
+        let sk_bytes = to_bytes(&sk);
+        assert_eq!(
+            SecretKey::<G1>::from_bytes(&sk_bytes).expect("Valid key."),
+            sk
+        );
+        let pk_bytes = to_bytes(&pk);
+        assert!(crate::public::PublicKey::<G1>::from_bytes(&pk_bytes).expect("Valid key.") == pk);
+        let mut bad = sk_bytes.clone();
+        bad[48..].copy_from_slice(&[0xffu8; 32]);
+        assert!(matches!(
+            SecretKey::<G1>::from_bytes(&bad),
+            Err(ElgamalError(InternalError::ScalarDecode { .. }))
+        ));
+        assert!(matches!(
+            crate::public::PublicKey::<G1>::from_bytes(&sk_bytes),
+            Err(ElgamalError(InternalError::WrongLength {
+                expected: 96,
+                got:      80,
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_decrypt_message_from_exponent() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate_all(&mut csprng);
+        let pk = crate::public::PublicKey::from(&sk);
+        for v in 0..20u64 {
+            let value = Value::from(v);
+            let c = pk.encrypt_exponent(&mut csprng, &value);
+            let m = sk.decrypt_message(&c);
+            assert_eq!(m, sk.decrypt(&c));
+            assert_eq!(m, Message::from_exponent(&sk.generator, &value));
+            assert_eq!(
+                *m.point(),
+                sk.generator.mul_by_scalar(&value),
+                "Decrypted point for {}.",
+                v
+            );
+        }
+    }
+
+    macro_rules! macro_test_matches_public {
+        ($function_name:ident, $curve_type:path) => {
+            #[test]
+            pub fn $function_name() {
+                let mut csprng = thread_rng();
+                let sk: SecretKey<$curve_type> = SecretKey::generate_all(&mut csprng);
+                let pk = PublicKey::from(&sk);
+                assert!(sk.matches_public(&pk));
+                assert_eq!(pk.generator_point(), &sk.generator);
+                let other = SecretKey::generate(&sk.generator, &mut csprng);
+                assert!(!sk.matches_public(&PublicKey::from(&other)));
+                let wrong_generator = PublicKey {
+                    generator: <$curve_type>::generate(&mut csprng),
+                    key:       pk.key,
+                };
+                assert!(!sk.matches_public(&wrong_generator));
+            }
+        };
+    }
+
+    macro_test_matches_public!(matches_public_g1, G1);
+    macro_test_matches_public!(matches_public_g2, G2);
+
     // Test serialiation of baby-step-giant-step since it is implemented manually.
     #[test]
     fn test_bsgs_serialize() {
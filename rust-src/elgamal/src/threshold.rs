@@ -0,0 +1,96 @@
+//! Threshold decryption of elgamal ciphers, where the secret key is shared
+//! among several parties, each of which produces a decryption share.
+
+use crate::{cipher::*, message::*, secret::*};
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::*;
+
+/// A Lagrange coefficient for reconstructing a shared secret key at zero. The
+/// coefficient of a share depends on the set of shares that is combined.
+pub type LagrangeCoeff<C> = <C as Curve>::Scalar;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, SerdeBase16Serialize)]
+#[repr(transparent)]
+/// Partial decryption of a cipher with a share of the secret key. This is
+/// `sk_i * c.0`, where `sk_i` is the share of the secret key.
+pub struct DecryptionShare<C: Curve> {
+    pub value: C,
+}
+
+impl<C: Curve> SecretKey<C> {
+    /// Produce a decryption share of the cipher, assuming this secret key is a
+    /// share of the key the cipher was encrypted with.
+    pub fn decrypt_share(&self, c: &Cipher<C>) -> DecryptionShare<C> {
+        DecryptionShare {
+            value: c.0.mul_by_scalar(&self.scalar),
+        }
+    }
+}
+
+/// Reconstruct the message from decryption shares and the Lagrange
+/// coefficients of the shares. The result is only meaningful if there are at
+/// least as many shares as the threshold of the sharing of the secret key, and
+/// the coefficients are computed for exactly the given set of shares.
+pub fn combine_shares<C: Curve>(
+    shares: &[(LagrangeCoeff<C>, DecryptionShare<C>)],
+    c: &Cipher<C>,
+) -> Message<C> {
+    let kag = shares.iter().fold(C::zero_point(), |acc, (coeff, share)| {
+        acc.plus_point(&share.value.mul_by_scalar(coeff))
+    });
+    Message {
+        value: c.1.minus_point(&kag),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::public::PublicKey;
+    use curve_arithmetic::secret_sharing::{lagrange_at_zero, share};
+    use ff::Field;
+    use pairing::bls12_381::{G1, G2};
+    use rand::*;
+
+    fn test_combine_shares_generic<C: Curve>() {
+        let mut csprng = thread_rng();
+        for _ in 0..10 {
+            let sk: SecretKey<C> = SecretKey::generate_all(&mut csprng);
+            let pk = PublicKey::from(&sk);
+            let msg = Message::generate(&mut csprng);
+            let c = pk.encrypt(&mut csprng, &msg);
+            let key_shares = share::<C, _>(&sk.scalar, 2, 3, &mut csprng);
+            for points in [[1, 2], [1, 3], [2, 3]].iter() {
+                let shares = points
+                    .iter()
+                    .map(|&i| {
+                        let sk_i = SecretKey {
+                            generator: sk.generator,
+                            scalar:    key_shares[i as usize - 1].1,
+                        };
+                        (lagrange_at_zero::<C>(points, i), sk_i.decrypt_share(&c))
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(combine_shares(&shares, &c), msg, "Reconstruction failed.");
+            }
+            // A single share is not enough.
+            let sk_1 = SecretKey {
+                generator: sk.generator,
+                scalar:    key_shares[0].1,
+            };
+            let shares = [(C::Scalar::one(), sk_1.decrypt_share(&c))];
+            assert_ne!(
+                combine_shares(&shares, &c),
+                msg,
+                "One share should not suffice."
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_combine_shares_g1() { test_combine_shares_generic::<G1>(); }
+
+    #[test]
+    pub fn test_combine_shares_g2() { test_combine_shares_generic::<G2>(); }
+}
@@ -3,7 +3,7 @@
 //! scheduler, and the mobile wallet.
 
 use crate::*;
-use crypto_common::*;
+use crypto_common::{rng::secure_rng, *};
 use ffi_helpers::*;
 use prelude::StdRng;
 use std::io::Cursor;
@@ -117,7 +117,7 @@ unsafe extern "C" fn make_encrypted_transfer_data(
 
     let input_amount = from_ptr!(input_amount_ptr);
 
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
 
     let data = match make_transfer_data(
         ctx,
@@ -231,7 +231,7 @@ unsafe extern "C" fn make_sec_to_pub_data(
 
     let input_amount = from_ptr!(input_amount_ptr);
 
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
 
     let data = match make_sec_to_pub_transfer_data(
         ctx,
@@ -383,13 +383,8 @@ unsafe extern "C" fn encrypt_amount(
 ) {
     let gc = from_ptr!(ctx_ptr);
     let pk = from_ptr!(pk_ptr);
-    let encrypted = crate::encrypt_amount(
-        gc,
-        pk,
-        Amount::from_micro_ccd(micro_ccd),
-        &mut rand::thread_rng(),
-    )
-    .0;
+    let encrypted =
+        crate::encrypt_amount(gc, pk, Amount::from_micro_ccd(micro_ccd), &mut secure_rng()).0;
     *out_high_ptr = Box::into_raw(Box::new(encrypted.encryptions[1]));
     *out_low_ptr = Box::into_raw(Box::new(encrypted.encryptions[0]));
 }
@@ -434,7 +429,7 @@ macro_derive_to_bytes!(Box elgamal_cipher_to_bytes, elgamal::Cipher<Group>);
 macro_free_ffi!(Box elgamal_cipher_free, elgamal::Cipher<Group>);
 #[no_mangle]
 pub extern "C" fn elgamal_cipher_gen() -> *mut elgamal::Cipher<Group> {
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     Box::into_raw(Box::new(elgamal::Cipher::generate(&mut csprng)))
 }
 
@@ -142,7 +142,7 @@ macro_rules! macro_generate_commitment_key {
         #[no_mangle]
         #[allow(clippy::not_unsafe_ptr_arg_deref)]
         extern "C" fn $function_name(n: size_t) -> *mut $type {
-            let mut csprng = thread_rng();
+            let mut csprng = crypto_common::rng::secure_rng();
             Box::into_raw(Box::new($generator(n, &mut csprng)))
         }
     };
@@ -13,7 +13,7 @@ use bulletproofs::{
     inner_product_proof::inner_product,
     range_proof::{prove_given_scalars as bulletprove, prove_less_than_or_equal, RangeProof},
 };
-use crypto_common::types::TransactionTime;
+use crypto_common::{rng::secure_rng, types::TransactionTime};
 use curve_arithmetic::{Curve, Pairing};
 use dodis_yampolskiy_prf as prf;
 use elgamal::{multicombine, Cipher};
@@ -88,7 +88,7 @@ pub fn generate_pio<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
     id_use_data: &IdObjectUseData<P, C>,
     initial_account: &impl InitialAccountDataWithSigning,
 ) -> Option<(PreIdentityObject<P, C>, ps_sig::SigRetrievalRandomness<P>)> {
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     let mut transcript = RandomOracle::domain("PreIdentityProof");
     // Prove ownership of the initial account
     let pub_info_for_ip = build_pub_info_for_ip(
@@ -181,7 +181,7 @@ pub fn generate_pio_v1<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
     threshold: Threshold,
     id_use_data: &IdObjectUseData<P, C>,
 ) -> Option<(PreIdentityObjectV1<P, C>, ps_sig::SigRetrievalRandomness<P>)> {
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     let mut transcript = RandomOracle::domain("PreIdentityProof");
     let CommonPioGenerationOutput {
         prover,
@@ -489,7 +489,7 @@ pub fn compute_sharing_data<'a, C: Curve>(
     commitment_key: &PedersenKey<C>,                    // commitment key
 ) -> SharingData<'a, C> {
     let n = ar_parameters.len() as u32;
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     // first commit to the scalar
     let (cmm_scalar, cmm_scalar_rand) = commitment_key.commit(&shared_scalar, &mut csprng);
     // We evaluate the polynomial at ar_identities.
@@ -571,7 +571,7 @@ pub fn compute_sharing_data_prf<'a, C: Curve>(
 ) -> SharingDataPrf<'a, C> {
     let commitment_key = &global_context.on_chain_commitment_key;
     let n = ar_parameters.len() as u32;
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     // first commit to the scalar
     let (cmm_scalar, cmm_scalar_rand) = commitment_key.commit(&shared_scalar, &mut csprng);
     // We evaluate the polynomial at ar_identities.
@@ -713,13 +713,14 @@ pub fn create_unsigned_credential<
 )>
 where
     AttributeType: Clone, {
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
 
     let (ip_sig, prio, alist) = (
         id_object.get_signature(),
         id_object.get_common_pio_fields(),
         id_object.get_attribute_list(),
     );
+    policy.validate(alist)?;
     let sig_retrieval_rand = &id_object_use_data.randomness;
     let aci = &id_object_use_data.aci;
 
@@ -1234,7 +1235,7 @@ pub fn generate_id_recovery_request<P: Pairing, C: Curve<Scalar = P::ScalarField
         secret: id_cred_sec.clone(),
     };
 
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     let mut transcript = RandomOracle::domain("IdRecoveryProof");
     transcript.append_message(b"ctx", &context);
     transcript.append_message(b"timestamp", &timestamp);
@@ -45,6 +45,9 @@ impl Display for CdiVerificationError {
 }
 /// Verify credential deployment info. This checks that the data is consistent,
 /// and that the credential is signed by the specified identity provider.
+///
+/// If several checks fail this returns the first failure in the order of
+/// [verify_cdi_collect_errors].
 pub fn verify_cdi<
     P: Pairing,
     C: Curve<Scalar = P::ScalarField>,
@@ -59,6 +62,32 @@ pub fn verify_cdi<
     cdi: &CredentialDeploymentInfo<P, C, AttributeType>,
     new_or_existing: &Either<TransactionTime, AccountAddress>,
 ) -> Result<(), CdiVerificationError> {
+    verify_cdi_collect_errors(global_context, ip_info, known_ars, cdi, new_or_existing)
+        .map_err(|errors| errors[0])
+}
+
+/// Same as [verify_cdi], but the independent checks of the credential, i.e.,
+/// the zero-knowledge proofs, the account ownership proof, and the policy, are
+/// done in parallel, and all of them are done even if some fail. The errors
+/// are returned in this fixed order, regardless of thread scheduling. The
+/// structural checks that are needed to construct the verifiers are done
+/// first, and only the first of them that fails is reported.
+///
+/// If the result is an error, the vector is not empty.
+pub fn verify_cdi_collect_errors<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+    A: HasArPublicKey<C>,
+>(
+    global_context: &GlobalContext<C>,
+    ip_info: &IpInfo<P>,
+    // NB: The following map only needs to be a superset of the ars
+    // in the cdi.
+    known_ars: &BTreeMap<ArIdentity, A>,
+    cdi: &CredentialDeploymentInfo<P, C, AttributeType>,
+    new_or_existing: &Either<TransactionTime, AccountAddress>,
+) -> Result<(), Vec<CdiVerificationError>> {
     // We need to check that the threshold is actually equal to
     // the number of coefficients in the sharing polynomial
     // (corresponding to the degree+1)
@@ -72,7 +101,7 @@ pub fn verify_cdi<
             .cmm_id_cred_sec_sharing_coeff
             .len()
     {
-        return Err(CdiVerificationError::Ar);
+        return Err(vec![CdiVerificationError::Ar]);
     }
     let on_chain_commitment_key = global_context.on_chain_commitment_key;
     let gens = global_context.bulletproof_generators();
@@ -118,7 +147,7 @@ pub fn verify_cdi<
     let verifier_sig = if let Some(v) = verifier_sig {
         v
     } else {
-        return Err(CdiVerificationError::Signature);
+        return Err(vec![CdiVerificationError::Signature]);
     };
 
     let witness_sig = cdi.proofs.id_proofs.proof_ip_sig.clone();
@@ -129,7 +158,8 @@ pub fn verify_cdi<
         &cdi.values.ar_data,
         &commitments.cmm_id_cred_sec_sharing_coeff,
         &cdi.proofs.id_proofs.proof_id_cred_pub,
-    )?;
+    )
+    .map_err(|e| vec![e])?;
 
     let verifier = AndAdapter {
         first:  verifier_reg_id,
@@ -148,41 +178,53 @@ pub fn verify_cdi<
         witness,
     };
 
-    if !verify(&mut ro, &verifier, &proof) {
-        return Err(CdiVerificationError::Proof);
-    }
-
-    if !verify_less_than_or_equal(
-        &mut ro,
-        8,
-        &cdi.proofs.id_proofs.commitments.cmm_cred_counter,
-        &cdi.proofs.id_proofs.commitments.cmm_max_accounts,
-        &cdi.proofs.id_proofs.cred_counter_less_than_max_accounts,
-        gens,
-        &on_chain_commitment_key,
-    ) {
-        return Err(CdiVerificationError::Proof);
-    }
-    let signed = utils::credential_hash_to_sign(cdv, &proofs.id_proofs, new_or_existing);
-    // Notice that here we provide all the verification keys, and the
-    // function `verify_accunt_ownership_proof` assumes that
-    // we have as many signatures as verification keys.
-    if !utils::verify_account_ownership_proof(
-        &cdv.cred_key_info.keys,
-        cdv.cred_key_info.threshold,
-        &proofs.proof_acc_sk,
-        signed.as_ref(),
-    ) {
-        return Err(CdiVerificationError::AccountOwnership);
-    }
-
-    let check_policy = verify_policy(&on_chain_commitment_key, commitments, &cdi.values.policy);
-
-    if !check_policy {
-        return Err(CdiVerificationError::Policy);
+    // The range proof is verified with the transcript that results from
+    // verifying the sigma protocol, so these two are checked sequentially. The
+    // account ownership proof and the policy are independent of them.
+    let check_proofs = || {
+        verify(&mut ro, &verifier, &proof)
+            && verify_less_than_or_equal(
+                &mut ro,
+                8,
+                &cdi.proofs.id_proofs.commitments.cmm_cred_counter,
+                &cdi.proofs.id_proofs.commitments.cmm_max_accounts,
+                &cdi.proofs.id_proofs.cred_counter_less_than_max_accounts,
+                gens,
+                &on_chain_commitment_key,
+            )
+    };
+    let check_account_ownership = || {
+        let signed = utils::credential_hash_to_sign(cdv, &proofs.id_proofs, new_or_existing);
+        // Notice that here we provide all the verification keys, and the
+        // function `verify_accunt_ownership_proof` assumes that
+        // we have as many signatures as verification keys.
+        utils::verify_account_ownership_proof(
+            &cdv.cred_key_info.keys,
+            cdv.cred_key_info.threshold,
+            &proofs.proof_acc_sk,
+            signed.as_ref(),
+        )
+    };
+    let check_policy = || verify_policy(&on_chain_commitment_key, commitments, &cdi.values.policy);
+
+    let (proofs_ok, (account_ownership_ok, policy_ok)) = rayon::join(check_proofs, || {
+        rayon::join(check_account_ownership, check_policy)
+    });
+
+    let errors = [
+        (proofs_ok, CdiVerificationError::Proof),
+        (account_ownership_ok, CdiVerificationError::AccountOwnership),
+        (policy_ok, CdiVerificationError::Policy),
+    ]
+    .iter()
+    .filter(|(ok, _)| !ok)
+    .map(|(_, e)| *e)
+    .collect::<Vec<_>>();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
     }
-
-    Ok(())
 }
 
 /// Verify initial account creation. This is essentially checking that the
@@ -250,6 +292,40 @@ fn id_cred_pub_verifier<C: Curve, A: HasArPublicKey<C>>(
     }))
 }
 
+/// Check that a credential reveals exactly the attributes of the given policy,
+/// with the same values and validity period, and that the revealed attributes
+/// are consistent with the commitments in the credential. This does not
+/// verify any proofs, so it is meant as a cheap check, e.g., by a wallet
+/// before submitting a credential. The revealed values are bound to the
+/// identity object by the proof of knowledge of the signature, which is
+/// checked by [verify_cdi].
+pub fn check_policy_consistency<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    cdi: &CredentialDeploymentInfo<P, C, AttributeType>,
+    policy: &Policy<C, AttributeType>,
+) -> Result<(), CdiVerificationError> {
+    let revealed = &cdi.values.policy;
+    if revealed.valid_to != policy.valid_to
+        || revealed.created_at != policy.created_at
+        || revealed.policy_vec != policy.policy_vec
+    {
+        return Err(CdiVerificationError::Policy);
+    }
+    // No attribute may be both revealed and committed to. verify_cdi checks
+    // this as part of the signature check.
+    let tags = revealed
+        .policy_vec
+        .keys()
+        .chain(cdi.proofs.id_proofs.commitments.cmm_attributes.keys());
+    if utils::encode_tags::<C::Scalar, _>(tags).is_err() {
+        return Err(CdiVerificationError::Policy);
+    }
+    Ok(())
+}
+
 /// Verify a policy. This currently does not do anything since
 /// the only check that is done is that the commitments are opened correctly,
 /// and that check is part of the signature check.
@@ -453,6 +529,7 @@ mod tests {
 
         // Testing with an existing RegId (i.e. an existing account)
         let existing_reg_id = account_address_from_registration_id(&cdi.values.cred_id);
+        let cdi_new = cdi;
         let cred_data = CredentialData {
             keys:      {
                 let mut keys = BTreeMap::new();
@@ -482,6 +559,40 @@ mod tests {
             &Right(existing_reg_id),
         );
         assert_eq!(cdi_check, Ok(()));
+
+        // Tampering with the proofs of the first credential by taking them from
+        // the second one. All the failures are reported.
+        let check =
+            |cdi| verify_cdi_collect_errors(&global_ctx, &ip_info, &ars_infos, cdi, &Left(EXPIRY));
+        assert_eq!(check(&cdi_new), Ok(()));
+        let mut tampered = cdi_new.clone();
+        tampered.proofs.proof_acc_sk = cdi.proofs.proof_acc_sk.clone();
+        assert_eq!(
+            check(&tampered),
+            Err(vec![CdiVerificationError::AccountOwnership])
+        );
+        // The account ownership proof signs the other proofs, so it fails too.
+        let mut tampered = cdi_new.clone();
+        tampered
+            .proofs
+            .id_proofs
+            .cred_counter_less_than_max_accounts = cdi
+            .proofs
+            .id_proofs
+            .cred_counter_less_than_max_accounts
+            .clone();
+        assert_eq!(
+            check(&tampered),
+            Err(vec![
+                CdiVerificationError::Proof,
+                CdiVerificationError::AccountOwnership
+            ])
+        );
+        assert_eq!(
+            verify_cdi(&global_ctx, &ip_info, &ars_infos, &tampered, &Left(EXPIRY)),
+            Err(CdiVerificationError::Proof),
+            "The first failure should be reported."
+        );
     }
 
     /// This tests the credential creation flow, where no initial account was
@@ -5,12 +5,11 @@ use crate::{
     constants::*,
     types::*,
 };
-use crypto_common::{size_t, types::TransactionTime, *};
+use crypto_common::{rng::secure_rng, size_t, types::TransactionTime, *};
 use either::Either::{Left, Right};
 use ffi_helpers::*;
 use pairing::bls12_381::{Bls12, G1};
 use pedersen_scheme::CommitmentKey as PedersenKey;
-use rand::thread_rng;
 use std::{collections::BTreeMap, convert::TryInto, io::Cursor};
 
 #[no_mangle]
@@ -121,7 +120,7 @@ macro_free_ffi!(Box pedersen_key_free, PedersenKey<G1>);
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn pedersen_key_gen() -> *mut PedersenKey<G1> {
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     Box::into_raw(Box::new(PedersenKey::generate(&mut csprng)))
 }
 
@@ -304,6 +303,7 @@ mod test {
     };
     use crypto_common::types::{KeyIndex, KeyPair};
     use dodis_yampolskiy_prf as prf;
+    use rand::thread_rng;
     use std::{collections::btree_map::BTreeMap, convert::TryFrom};
 
     type ExampleAttributeList = AttributeList<BaseField, AttributeKind>;
@@ -328,6 +328,9 @@ mod test {
             ip_secret_key,
             ip_cdi_secret_key,
         } = test_create_ip_info(&mut csprng, num_ars, max_attrs);
+        // Use the identity provider's public information as distributed, i.e.,
+        // after a trip through its binary serialization.
+        let ip_info = serialize_deserialize(&ip_info).expect("IpInfo should round-trip.");
 
         let prf_key = prf::SecretKey::generate(&mut csprng);
 
@@ -453,17 +456,22 @@ mod test {
         )
         .expect("Should generate the credential successfully.");
 
-        let (wrong_cdi, _) = create_credential(
+        // A policy revealing a value other than the signed one is rejected
+        // when creating the credential.
+        assert!(create_credential(
             context,
             &id_object,
             &id_use_data,
             0,
-            wrong_policy,
+            wrong_policy.clone(),
             &acc_data,
             &SystemAttributeRandomness {},
             &Left(EXPIRY),
         )
-        .expect("Should generate the credential successfully.");
+        .is_err());
+        // and by the verifier if the revealed value is changed afterwards.
+        let mut wrong_cdi = cdi.clone();
+        wrong_cdi.values.policy = wrong_policy;
 
         let cdi_bytes = to_bytes(&cdi);
         let cdi_bytes_len = cdi_bytes.len() as size_t;
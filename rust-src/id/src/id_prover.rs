@@ -15,6 +15,7 @@ use bulletproofs::{
     set_non_membership_proof::prove as prove_set_non_membership,
     utils::Generators,
 };
+use crypto_common::rng::secure_rng;
 use curve_arithmetic::{Curve, Value};
 use ed25519_dalek as ed25519;
 use ff::Field;
@@ -44,7 +45,7 @@ impl<C: Curve, AttributeType: Attribute<C::Scalar>> StatementWithContext<C, Attr
         transcript.append_message(b"ctx", &global);
         transcript.add_bytes(challenge);
         transcript.append_message(b"credential", &self.credential);
-        let mut csprng = rand::thread_rng();
+        let mut csprng = secure_rng();
         for atomic_statement in self.statement.statements.iter() {
             match atomic_statement {
                 AtomicStatement::RevealAttribute { statement } => {
@@ -197,7 +198,7 @@ pub fn prove_attribute_in_range<C: Curve, AttributeType: Attribute<C::Scalar>>(
     r: &PedersenRandomness<C>,
 ) -> Option<RangeProof<C>> {
     let mut transcript = RandomOracle::domain("attribute_range_proof");
-    let mut csprng = rand::thread_rng();
+    let mut csprng = secure_rng();
     let delta = attribute.to_field_element();
     let a = lower.to_field_element();
     let b = upper.to_field_element();
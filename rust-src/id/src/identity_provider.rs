@@ -7,12 +7,11 @@ use crate::{
     utils,
 };
 use bulletproofs::range_proof::verify_efficient;
-use crypto_common::{to_bytes, types::TransactionTime};
+use crypto_common::{rng::secure_rng, to_bytes, types::TransactionTime};
 use curve_arithmetic::{multiexp, Curve, Pairing};
 use elgamal::multicombine;
 use ff::Field;
 use pedersen_scheme::{Commitment, CommitmentKey};
-use rand::*;
 use random_oracle::RandomOracle;
 use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, BTreeSet};
@@ -414,7 +413,7 @@ fn sign_identity_object_common<
         alist,
         &ip_info.ip_verify_key,
     )?;
-    let mut csprng = thread_rng();
+    let mut csprng = secure_rng();
     // FIXME: Pass in csprng here.
     Ok(ip_secret_key.sign_unknown_message(&message, &mut csprng))
 }
@@ -676,6 +675,7 @@ mod tests {
     use crypto_common::types::{KeyIndex, KeyPair};
     use ff::Field;
     use pedersen_scheme::{CommitmentKey, Value as PedersenValue};
+    use rand::*;
     use std::collections::btree_map::BTreeMap;
 
     const EXPIRY: TransactionTime = TransactionTime {
@@ -776,6 +776,67 @@ mod tests {
         assert!(ver_ok.is_ok());
     }
 
+    /// Check that verify_credentials distinguishes an invalid proof from
+    /// inconsistent anonymity revocation data.
+    #[test]
+    fn test_verify_credentials_fail_reasons() {
+        let max_attrs = 10;
+        let num_ars = 4;
+        let mut csprng = thread_rng();
+        let IpData {
+            public_ip_info: ip_info,
+            ip_secret_key,
+            ip_cdi_secret_key,
+        } = test_create_ip_info(&mut csprng, num_ars, max_attrs);
+        let global_ctx = GlobalContext::<ArCurve>::generate(String::from("genesis_string"));
+        let (ars_infos, _) =
+            test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
+        let id_use_data = test_create_id_use_data(&mut csprng);
+        let acc_data = InitialAccountData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+        let (context, pio, _) = test_create_pio(
+            &id_use_data,
+            &ip_info,
+            &ars_infos,
+            &global_ctx,
+            num_ars,
+            &acc_data,
+        );
+        let attrs = test_create_attributes();
+        let verify = |pio| {
+            verify_credentials(
+                pio,
+                context,
+                &attrs,
+                EXPIRY,
+                &ip_secret_key,
+                &ip_cdi_secret_key,
+            )
+            .map(|_| ())
+        };
+
+        // Swapping two range proofs keeps the data consistent, but the
+        // proofs no longer verify.
+        let mut bad_proof = pio.clone();
+        bad_proof.poks.common_proof_fields.bulletproofs.swap(0, 1);
+        assert_eq!(verify(&bad_proof), Err(Reason::IncorrectProof));
+
+        // Dropping the data for one anonymity revoker makes it inconsistent
+        // with the chosen anonymity revokers.
+        let mut bad_ar_data = pio.clone();
+        let first = *bad_ar_data.ip_ar_data.keys().next().unwrap();
+        bad_ar_data.ip_ar_data.remove(&first);
+        assert_eq!(verify(&bad_ar_data), Err(Reason::WrongArParameters));
+
+        assert_eq!(verify(&pio), Ok(()));
+    }
+
     #[test]
     fn test_verify_credentials_success_v1() {
         // Arrange (create identity provider and PreIdentityObject, and verify validity)
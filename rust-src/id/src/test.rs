@@ -280,6 +280,52 @@ pub fn test_pipeline() {
     let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &cdi, &Left(EXPIRY));
     assert_eq!(cdi_check, Ok(()));
 
+    // Check the revealed attributes against the intended policy.
+    assert_eq!(check_policy_consistency(&cdi, &policy), Ok(()));
+    let mut wrong_policy = policy.clone();
+    wrong_policy
+        .policy_vec
+        .insert(AttributeTag::from(8u8), AttributeKind::from(32));
+    assert_eq!(
+        check_policy_consistency(&cdi, &wrong_policy),
+        Err(CdiVerificationError::Policy),
+        "A wrong attribute value should be detected."
+    );
+    let mut committed_tag = cdi.clone();
+    let (&tag, _) = committed_tag
+        .proofs
+        .id_proofs
+        .commitments
+        .cmm_attributes
+        .iter()
+        .next()
+        .expect("Some attributes are committed to.");
+    committed_tag
+        .values
+        .policy
+        .policy_vec
+        .insert(tag, AttributeKind::from(1));
+    let mut committed_policy = policy.clone();
+    committed_policy
+        .policy_vec
+        .insert(tag, AttributeKind::from(1));
+    assert_eq!(
+        check_policy_consistency(&committed_tag, &committed_policy),
+        Err(CdiVerificationError::Policy),
+        "An attribute cannot be both revealed and committed to."
+    );
+    // verify_cdi detects the same inconsistency while checking the signature.
+    assert_eq!(
+        verify_cdi(
+            &global_ctx,
+            &ip_info,
+            &ars_infos,
+            &committed_tag,
+            &Left(EXPIRY)
+        ),
+        Err(CdiVerificationError::Signature)
+    );
+
     // Verify serialization
     let cdi_values = serialize_deserialize(&cdi.values);
     assert!(
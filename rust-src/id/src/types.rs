@@ -45,6 +45,7 @@ use std::{
     str::FromStr,
 };
 use thiserror::Error;
+use zeroize::Zeroizing;
 
 /// NB: This includes digits of PI (starting with 314...) as ASCII characters
 /// this could be what is desired, but it is important to be aware of it.
@@ -587,11 +588,23 @@ pub struct AttributeList<F: Field, AttributeType: Attribute<F>> {
     pub _phantom:     std::marker::PhantomData<F>,
 }
 
+impl<F: Field, AttributeType: Attribute<F>> AttributeList<F, AttributeType> {
+    /// Get the value of the attribute with the given tag, if present.
+    pub fn get(&self, attribute_tag: AttributeTag) -> Option<&AttributeType> {
+        self.alist.get(&attribute_tag)
+    }
+
+    /// Iterate over the attributes in the list, in increasing order of tags.
+    pub fn iter(&self) -> impl Iterator<Item = (AttributeTag, &AttributeType)> {
+        self.alist.iter().map(|(tag, value)| (*tag, value))
+    }
+}
+
 impl<F: Field, AttributeType: Attribute<F>> HasAttributeValues<F, AttributeType>
     for AttributeList<F, AttributeType>
 {
     fn get_attribute_value(&self, attribute_tag: AttributeTag) -> Option<&AttributeType> {
-        self.alist.get(&attribute_tag)
+        self.get(attribute_tag)
     }
 }
 
@@ -616,6 +629,24 @@ impl<C: Curve> IdCredentials<C> {
             id_cred_sec: PedersenValue::generate(csprng),
         }
     }
+
+    /// The public id credentials `g^id_cred_sec` for the given generator. On
+    /// chain the generator is the `g` of the on-chain commitment key.
+    pub fn id_cred_pub(&self, g: &C) -> C { g.mul_by_scalar(&self.id_cred_sec) }
+
+    /// Serialize only the public part of the credentials, i.e., the public id
+    /// credentials for the given generator. The result never contains the
+    /// secret.
+    pub fn public_to_bytes(&self, g: &C) -> Vec<u8> { to_bytes(&self.id_cred_pub(g)) }
+
+    /// Serialize the secret id credentials into a buffer that is zeroed when
+    /// dropped. This is the only place where the secret should be exported
+    /// separately from the rest of the credential holder information.
+    pub fn export_secret(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(Vec::with_capacity(C::SCALAR_LENGTH));
+        self.id_cred_sec.serial(&mut *out);
+        out
+    }
 }
 
 /// Private credential holder information. A user maintaints these
@@ -1360,6 +1391,38 @@ pub struct Policy<C: Curve, AttributeType: Attribute<C::Scalar>> {
     pub _phantom:   std::marker::PhantomData<C>,
 }
 
+#[derive(Debug, Error, PartialEq, Eq)]
+/// Reasons why a policy is inconsistent with the attribute list it reveals
+/// attributes of.
+pub enum PolicyError {
+    #[error("The policy reveals attribute {0}, which is not in the attribute list.")]
+    UnknownAttribute(AttributeTag),
+    #[error("The policy reveals a different value of attribute {0} than the attribute list.")]
+    ValueMismatch(AttributeTag),
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Policy<C, AttributeType> {
+    /// Check that every revealed attribute is in the attribute list, with the
+    /// same value. Otherwise a credential with this policy would reveal a
+    /// value that is not the one the identity provider signed.
+    ///
+    /// The revealed attributes are a map, so a policy cannot reveal the same
+    /// tag twice, and deserialization rejects duplicate tags.
+    pub fn validate(
+        &self,
+        alist: &AttributeList<C::Scalar, AttributeType>,
+    ) -> Result<(), PolicyError> {
+        for (&tag, value) in self.policy_vec.iter() {
+            match alist.get(tag) {
+                None => return Err(PolicyError::UnknownAttribute(tag)),
+                Some(v) if v != value => return Err(PolicyError::ValueMismatch(tag)),
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<C: Curve, AttributeType: Attribute<C::Scalar>> Serial for Policy<C, AttributeType> {
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.put(&self.valid_to);
@@ -2388,7 +2451,7 @@ impl<C: Curve> HasAttributeRandomness<C> for SystemAttributeRandomness {
         &self,
         _attribute_tag: AttributeTag,
     ) -> Result<PedersenRandomness<C>, Self::ErrorType> {
-        let mut csprng = rand::thread_rng();
+        let mut csprng = crypto_common::rng::secure_rng();
         Ok(PedersenRandomness::generate(&mut csprng))
     }
 }
@@ -2417,6 +2480,140 @@ mod tests {
     use super::*;
     use ed25519::Signer;
 
+    #[test]
+    fn test_attribute_list_get() {
+        use crate::constants::AttributeKind;
+        let lei: AttributeTag = "lei".parse().expect("lei is a known attribute.");
+        let alist = AttributeList::<pairing::bls12_381::Fr, AttributeKind> {
+            valid_to:     YearMonth::new(2030, 1).unwrap(),
+            created_at:   YearMonth::new(2020, 1).unwrap(),
+            max_accounts: 200,
+            alist:        vec![
+                (lei, AttributeKind::from(13)),
+                (AttributeTag(0), AttributeKind::from(0)),
+            ]
+            .into_iter()
+            .collect(),
+            _phantom:     Default::default(),
+        };
+        assert_eq!(alist.get(lei), Some(&AttributeKind::from(13)));
+        assert_eq!(alist.get(AttributeTag(1)), None, "Absent attribute.");
+        assert_eq!(
+            alist.iter().collect::<Vec<_>>(),
+            vec![
+                (AttributeTag(0), &AttributeKind::from(0)),
+                (lei, &AttributeKind::from(13))
+            ],
+            "Attributes are ordered by tag."
+        );
+    }
+
+    #[test]
+    fn test_ip_info_ar_info_serialization() {
+        use crate::test::{test_create_ars, test_create_ip_info};
+        let mut csprng = rand::thread_rng();
+        let ip_info = test_create_ip_info(&mut csprng, 3, 5).public_ip_info;
+        let bytes = to_bytes(&ip_info);
+        let ip_info2: IpInfo<pairing::bls12_381::Bls12> =
+            from_bytes(&mut std::io::Cursor::new(&bytes)).expect("IpInfo should deserialize.");
+        assert_eq!(ip_info2.ip_identity, ip_info.ip_identity);
+        assert_eq!(ip_info2.ip_description, ip_info.ip_description);
+        assert_eq!(ip_info2.ip_verify_key, ip_info.ip_verify_key);
+        assert_eq!(ip_info2.ip_cdi_verify_key, ip_info.ip_cdi_verify_key);
+        assert!(
+            from_bytes::<IpInfo<pairing::bls12_381::Bls12>, _>(&mut std::io::Cursor::new(
+                &bytes[..bytes.len() - 1]
+            ))
+            .is_err(),
+            "Truncated IpInfo should be rejected."
+        );
+
+        let g = pairing::bls12_381::G1::generate(&mut csprng);
+        let (ar_infos, _) = test_create_ars(&g, 3, &mut csprng);
+        for ar_info in ar_infos.values() {
+            let ar_info2 = serialize_deserialize(ar_info).expect("ArInfo should round-trip.");
+            assert_eq!(&ar_info2, ar_info);
+        }
+        // An ArInfo whose public key is not a valid point is rejected.
+        let mut bytes = to_bytes(&ar_infos[&ArIdentity::new(1)]);
+        let len = bytes.len();
+        bytes[len - 48..].iter_mut().for_each(|b| *b = 0xff);
+        assert!(
+            from_bytes::<ArInfo<pairing::bls12_381::G1>, _>(&mut std::io::Cursor::new(&bytes))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_policy_validate() {
+        use crate::constants::AttributeKind;
+        use pairing::bls12_381::{Fr, G1};
+        let alist = AttributeList::<Fr, AttributeKind> {
+            valid_to:     YearMonth::new(2030, 1).unwrap(),
+            created_at:   YearMonth::new(2020, 1).unwrap(),
+            max_accounts: 200,
+            alist:        vec![
+                (AttributeTag(0), AttributeKind::from(0)),
+                (AttributeTag(3), AttributeKind::from(3)),
+            ]
+            .into_iter()
+            .collect(),
+            _phantom:     Default::default(),
+        };
+        let policy = |revealed: Vec<(u8, u64)>| Policy::<G1, AttributeKind> {
+            valid_to:   alist.valid_to,
+            created_at: alist.created_at,
+            policy_vec: revealed
+                .into_iter()
+                .map(|(tag, v)| (AttributeTag(tag), AttributeKind::from(v)))
+                .collect(),
+            _phantom:   Default::default(),
+        };
+        assert_eq!(policy(vec![]).validate(&alist), Ok(()));
+        assert_eq!(policy(vec![(0, 0), (3, 3)]).validate(&alist), Ok(()));
+        assert_eq!(
+            policy(vec![(0, 0), (1, 1)]).validate(&alist),
+            Err(PolicyError::UnknownAttribute(AttributeTag(1)))
+        );
+        assert_eq!(
+            policy(vec![(3, 4)]).validate(&alist),
+            Err(PolicyError::ValueMismatch(AttributeTag(3)))
+        );
+        // A serialized policy that reveals the same tag twice is rejected.
+        let mut bytes = to_bytes(&policy(vec![(0, 0)]));
+        let entry = bytes[bytes.len() - 3..].to_vec();
+        bytes.extend_from_slice(&entry);
+        let len_index = bytes.len() - 2 * entry.len() - 2;
+        bytes[len_index + 1] = 2;
+        assert!(
+            from_bytes::<Policy<G1, AttributeKind>, _>(&mut std::io::Cursor::new(&bytes)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_id_credentials_public_to_bytes() {
+        use pairing::bls12_381::G1;
+        use rand::thread_rng;
+
+        let mut csprng = thread_rng();
+        let g = G1::generate(&mut csprng);
+        for _ in 0..100 {
+            let id_cred = IdCredentials::<G1>::generate(&mut csprng);
+            let public = id_cred.public_to_bytes(&g);
+            let secret = id_cred.export_secret();
+            assert_eq!(
+                public,
+                to_bytes(&g.mul_by_scalar(&id_cred.id_cred_sec)),
+                "Public serialization should be the public id credentials."
+            );
+            assert_eq!(secret.len(), G1::SCALAR_LENGTH);
+            assert!(
+                !public.windows(secret.len()).any(|w| w == &secret[..]),
+                "Public serialization contains the secret."
+            );
+        }
+    }
+
     #[test]
     fn test_serde_sig() {
         use rand::thread_rng;
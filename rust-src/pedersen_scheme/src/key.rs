@@ -6,6 +6,7 @@ use curve_arithmetic::*;
 
 use crypto_common::*;
 use crypto_common_derive::*;
+use ff::Field;
 use rand::*;
 
 /// A commitment key is a pair of group elements that are used as a base to
@@ -21,8 +22,8 @@ pub struct CommitmentKey<C: Curve> {
 impl<C: Curve> CommitmentKey<C> {
     pub fn new(g: C, h: C) -> Self { CommitmentKey { g, h } }
 
-    /// Commit to the given value using a freshly generated randomness, and
-    /// return the randomness that was generated.
+    /// Commit to the given value using a freshly generated non-zero
+    /// randomness, and return the randomness that was generated.
     pub fn commit<T, V: AsRef<C::Scalar>>(
         &self,
         s: &V,
@@ -30,7 +31,7 @@ impl<C: Curve> CommitmentKey<C> {
     ) -> (Commitment<C>, Randomness<C>)
     where
         T: Rng, {
-        let r = Randomness::<C>::generate(csprng);
+        let r = Randomness::<C>::generate_non_zero(csprng);
         (self.hide(s, &r), r)
     }
 
@@ -58,6 +59,88 @@ impl<C: Curve> CommitmentKey<C> {
         self.hide(s, r) == *c
     }
 
+    /// Check that the commitment is to the given values with the given
+    /// randomness. The comparison is in constant time. The key commits to a
+    /// single value, so this returns `false` unless exactly one value is given.
+    /// The length of `values` is not considered secret.
+    pub fn open_values(
+        &self,
+        commitment: &Commitment<C>,
+        values: &[C::Scalar],
+        randomness: &C::Scalar,
+    ) -> bool {
+        match values {
+            [value] => ct::ct_eq_bytes(
+                &to_bytes(&self.hide_worker(value, randomness)),
+                &to_bytes(commitment),
+            ),
+            _ => false,
+        }
+    }
+
+    /// Check a batch of openings, each consisting of a commitment, the
+    /// committed values, and the randomness, as in
+    /// [open_values](Self::open_values). The openings are combined with fresh
+    /// random coefficients drawn from `csprng`, so that all of them are
+    /// checked with a single multi-exponentiation. If that check fails each
+    /// opening is checked individually, and the indices of the ones that do
+    /// not hold are returned in increasing order.
+    ///
+    /// Unlike [open_values](Self::open_values) this is not constant time.
+    #[allow(clippy::type_complexity)]
+    pub fn batch_open<T: Rng>(
+        &self,
+        items: &[(Commitment<C>, &[C::Scalar], C::Scalar)],
+        csprng: &mut T,
+    ) -> Result<(), Vec<usize>> {
+        // Openings that do not consist of exactly one value cannot hold.
+        let mut malformed = Vec::new();
+        let mut points = Vec::with_capacity(items.len() + 2);
+        points.push(self.g);
+        points.push(self.h);
+        let mut exps = Vec::with_capacity(items.len() + 2);
+        exps.push(C::Scalar::zero());
+        exps.push(C::Scalar::zero());
+        for (i, (commitment, values, randomness)) in items.iter().enumerate() {
+            let value = match values {
+                [value] => value,
+                _ => {
+                    malformed.push(i);
+                    continue;
+                }
+            };
+            // Add r_i * (value * g + randomness * h - commitment), which is 0
+            // if the opening holds.
+            let r = C::generate_non_zero_scalar(csprng);
+            let mut x = *value;
+            x.mul_assign(&r);
+            exps[0].add_assign(&x);
+            let mut y = *randomness;
+            y.mul_assign(&r);
+            exps[1].add_assign(&y);
+            let mut neg_r = r;
+            neg_r.negate();
+            points.push(commitment.0);
+            exps.push(neg_r);
+        }
+        if multiexp(&points, &exps).is_zero_point() {
+            return if malformed.is_empty() {
+                Ok(())
+            } else {
+                Err(malformed)
+            };
+        }
+        let failed = items
+            .iter()
+            .enumerate()
+            .filter(|(_, (commitment, values, randomness))| {
+                !matches!(values, [value] if self.hide_worker(value, randomness) == *commitment)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        Err(failed)
+    }
+
     pub fn generate<T>(csprng: &mut T) -> CommitmentKey<C>
     where
         T: Rng, {
@@ -92,6 +175,42 @@ mod tests {
 
     macro_test_key_byte_conversion!(key_byte_conversion_bls12_381_g2_affine, G2Affine);
 
+    #[test]
+    pub fn key_serialization_roundtrip() {
+        let mut csprng = thread_rng();
+        assert_serialization_roundtrip(&mut csprng, 20, CommitmentKey::<G1>::generate);
+    }
+
+    // Check that keys and commitments whose points are not in the group are
+    // rejected when deserializing.
+    #[test]
+    pub fn key_and_commitment_reject_invalid_points() {
+        let mut csprng = thread_rng();
+        let key = CommitmentKey::<G1>::generate(&mut csprng);
+        let (c, _) = key.commit(&Value::<G1>::generate(&mut csprng), &mut csprng);
+        assert_eq!(serialize_deserialize(&c).expect("Valid commitment."), c);
+        let key_bytes = to_bytes(&key);
+        assert_eq!(key_bytes.len(), 2 * G1::GROUP_ELEMENT_LENGTH);
+        // A compressed point whose x coordinate is larger than the field
+        // modulus is not a valid encoding.
+        let mut point = vec![0xff; G1::GROUP_ELEMENT_LENGTH];
+        point[0] = 0x9f;
+        for i in 0..2 {
+            let mut bad = key_bytes.clone();
+            bad[i * G1::GROUP_ELEMENT_LENGTH..(i + 1) * G1::GROUP_ELEMENT_LENGTH]
+                .copy_from_slice(&point);
+            assert!(
+                from_bytes::<CommitmentKey<G1>, _>(&mut std::io::Cursor::new(&bad)).is_err(),
+                "Point {} of the key is invalid.",
+                i
+            );
+        }
+        assert!(
+            from_bytes::<Commitment<G1>, _>(&mut std::io::Cursor::new(&point)).is_err(),
+            "Commitment is invalid."
+        );
+    }
+
     macro_rules! macro_test_commit_open {
         ($function_name:ident, $curve_type:path) => {
             #[test]
@@ -109,6 +228,47 @@ mod tests {
         };
     }
 
+    #[test]
+    pub fn open_values() {
+        let mut csprng = thread_rng();
+        let key = CommitmentKey::<G1>::generate(&mut csprng);
+        let value = G1::generate_scalar(&mut csprng);
+        let randomness = G1::generate_scalar(&mut csprng);
+        let c = key.hide_worker(&value, &randomness);
+        assert!(key.open_values(&c, &[value], &randomness));
+        let other = G1::generate_scalar(&mut csprng);
+        assert!(!key.open_values(&c, &[other], &randomness), "Wrong value.");
+        assert!(!key.open_values(&c, &[value], &other), "Wrong randomness.");
+        assert!(!key.open_values(&c, &[], &randomness), "Too few values.");
+        assert!(
+            !key.open_values(&c, &[value, other], &randomness),
+            "Too many values."
+        );
+    }
+
+    #[test]
+    pub fn batch_open() {
+        let mut csprng = thread_rng();
+        let key = CommitmentKey::<G1>::generate(&mut csprng);
+        let openings: Vec<_> = (0..10)
+            .map(|_| {
+                let value = G1::generate_scalar(&mut csprng);
+                let randomness = G1::generate_scalar(&mut csprng);
+                (key.hide_worker(&value, &randomness), [value], randomness)
+            })
+            .collect();
+        let mut items: Vec<_> = openings
+            .iter()
+            .map(|(c, values, randomness)| (*c, &values[..], *randomness))
+            .collect();
+        assert_eq!(key.batch_open(&items, &mut csprng), Ok(()));
+        assert_eq!(key.batch_open(&[], &mut csprng), Ok(()));
+        items[6].2 = G1::generate_scalar(&mut csprng);
+        assert_eq!(key.batch_open(&items, &mut csprng), Err(vec![6]));
+        items[2].1 = &[];
+        assert_eq!(key.batch_open(&items, &mut csprng), Err(vec![2, 6]));
+    }
+
     macro_test_commit_open!(commit_open_bls12_381_g1_affine, G1Affine);
     macro_test_commit_open!(commit_open_bls12_381_g1_projectitve, G1);
 
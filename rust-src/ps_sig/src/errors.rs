@@ -17,6 +17,8 @@ pub(crate) enum InternalError {
     SecretKeyLengthError,
     #[error("Wrong length of public key.")]
     PublicKeyLengthError,
+    #[error("Could not decode element {index} of the public key.")]
+    PublicKeyElementError { index: usize },
     #[error("Wrong length of message vec bytes.")]
     MessageVecLengthError,
     #[error("Wrong length of message.")]
@@ -4,16 +4,14 @@
 
 use rand::*;
 
-use crate::{known_message::*, signature::*};
+use crate::{errors::*, known_message::*, signature::*};
 use crypto_common::*;
 use crypto_common_derive::*;
 use curve_arithmetic::*;
 
-use crate::secret::*;
-
 /// PS public key. The documentation of the fields
 /// assumes the secret key is $(x, y_1, ..., y_n)$ (see specification).
-#[derive(Debug, Clone, Serialize, SerdeBase16Serialize)]
+#[derive(Debug, Clone, Serial, SerdeBase16Serialize)]
 pub struct PublicKey<C: Pairing> {
     /// Generator of G1
     pub g:        C::G1,
@@ -22,7 +20,8 @@ pub struct PublicKey<C: Pairing> {
     /// Generator $g_1$ raised to the powers $y_i$
     #[size_length = 4]
     pub ys:       Vec<C::G1>,
-    /// Generator $g_2$ raised to the powers $y_i$
+    /// Generator $g_2$ raised to the powers $y_i$. Always of the same length
+    /// as `ys`.
     #[size_length = 4]
     pub y_tildas: Vec<C::G2>,
     /// Generator $g_2$ raised to the power $x$.
@@ -43,14 +42,32 @@ impl<C: Pairing> Eq for PublicKey<C> {}
 
 #[allow(clippy::len_without_is_empty)]
 impl<C: Pairing> PublicKey<C> {
+    /// Length of the serialization of the parts of the key that do not depend
+    /// on the number of messages, i.e., g, g_tilda, x_tilda, and the two length
+    /// prefixes.
+    const FIXED_BYTE_LEN: usize =
+        C::G1::GROUP_ELEMENT_LENGTH + 2 * C::G2::GROUP_ELEMENT_LENGTH + 2 * 4;
+    /// Length of the serialization of the parts of the key for each message.
+    const PER_MESSAGE_BYTE_LEN: usize = C::G1::GROUP_ELEMENT_LENGTH + C::G2::GROUP_ELEMENT_LENGTH;
+
     /// Return the number of commitments that can be signed with this key.
     pub fn len(&self) -> usize { self.ys.len() }
 
+    /// Same as [PublicKey::len]. The number of messages that can be signed with
+    /// this key.
+    pub fn message_count(&self) -> usize { self.len() }
+
+    /// The length in bytes of the serialization of this key. This is exact,
+    /// so it can be used to preallocate buffers or validate lengths.
+    pub fn byte_len(&self) -> usize {
+        Self::FIXED_BYTE_LEN + self.message_count() * Self::PER_MESSAGE_BYTE_LEN
+    }
+
     pub fn verify(&self, sig: &Signature<C>, message: &KnownMessage<C>) -> bool {
         let ys = &self.y_tildas;
         let x = self.x_tilda;
         let ms = &message.0;
-        if sig.0.is_zero_point() || ms.len() > ys.len() {
+        if sig.0.require_non_identity().is_err() || ms.len() > ys.len() {
             return false;
         }
         let h = ys
@@ -64,6 +81,88 @@ impl<C: Pairing> PublicKey<C> {
         C::check_pairing_eq(&sig.0, &hx, &sig.1, &self.g_tilda)
     }
 
+    /// Deserialize a public key for signing `n` commitments. This reads the
+    /// same format as the [Deserial] instance, but additionally requires both
+    /// vectors to be of length `n`. If an element cannot be decoded the error
+    /// reports its index, counting `g`, `g_tilda`, `ys`, `y_tildas`, and
+    /// `x_tilda` in that order.
+    pub fn deserial_with_count<R: ReadBytesExt>(
+        source: &mut R,
+        n: usize,
+    ) -> Result<PublicKey<C>, SignatureError> {
+        Self::deserial_worker(source, Some(n))
+    }
+
+    /// Worker for [deserial_with_count](Self::deserial_with_count) and the
+    /// [Deserial] instance. If `n` is not given it is the first length in the
+    /// input, and the second length must match it.
+    fn deserial_worker<R: ReadBytesExt>(
+        source: &mut R,
+        n: Option<usize>,
+    ) -> Result<PublicKey<C>, SignatureError> {
+        fn element<R: ReadBytesExt, T: Deserial>(
+            source: &mut R,
+            index: &mut usize,
+        ) -> Result<T, SignatureError> {
+            let x = source.get().map_err(|_| {
+                SignatureError(InternalError::PublicKeyElementError { index: *index })
+            })?;
+            *index += 1;
+            Ok(x)
+        }
+        fn elements<R: ReadBytesExt, T: Deserial>(
+            source: &mut R,
+            n: usize,
+            index: &mut usize,
+        ) -> Result<Vec<T>, SignatureError> {
+            charge_deserial_budget::<T>(n)
+                .map_err(|_| SignatureError(InternalError::PublicKeyLengthError))?;
+            let mut out = safe_with_capacity(n);
+            for _ in 0..n {
+                out.push(element(source, index)?);
+            }
+            Ok(out)
+        }
+        fn count<R: ReadBytesExt>(
+            source: &mut R,
+            n: Option<usize>,
+        ) -> Result<usize, SignatureError> {
+            let len: ParseResult<u32> = source.get();
+            match (len, n) {
+                (Ok(len), None) => Ok(len as usize),
+                (Ok(len), Some(n)) if len as usize == n => Ok(n),
+                _ => Err(SignatureError(InternalError::PublicKeyLengthError)),
+            }
+        }
+        let mut index = 0;
+        let g = element(source, &mut index)?;
+        let g_tilda = element(source, &mut index)?;
+        let n = count(source, n)?;
+        let ys = elements(source, n, &mut index)?;
+        count(source, Some(n))?;
+        let y_tildas = elements(source, n, &mut index)?;
+        let x_tilda = element(source, &mut index)?;
+        Ok(PublicKey {
+            g,
+            g_tilda,
+            ys,
+            y_tildas,
+            x_tilda,
+        })
+    }
+
+    /// Deserialize a public key from exactly the given bytes. The number of
+    /// commitments the key can sign is inferred from the length of the input.
+    pub fn from_bytes(bytes: &[u8]) -> Result<PublicKey<C>, SignatureError> {
+        let fixed = Self::FIXED_BYTE_LEN;
+        let per_commitment = Self::PER_MESSAGE_BYTE_LEN;
+        let n = bytes.len().saturating_sub(fixed) / per_commitment;
+        if bytes.len() != fixed + n * per_commitment {
+            return Err(SignatureError(InternalError::PublicKeyLengthError));
+        }
+        Self::deserial_with_count(&mut std::io::Cursor::new(bytes), n)
+    }
+
     /// Generate a public key  from a `csprng`.
     pub fn arbitrary<T>(n: usize, csprng: &mut T) -> PublicKey<C>
     where
@@ -88,25 +187,18 @@ impl<C: Pairing> PublicKey<C> {
     }
 }
 
-impl<C: Pairing> From<&SecretKey<C>> for PublicKey<C> {
-    /// Derive this public key from its corresponding `SecretKey`.
-    fn from(sk: &SecretKey<C>) -> PublicKey<C> {
-        let ys = sk.ys.iter().map(|r| sk.g.mul_by_scalar(r)).collect();
-        let y_tildas = sk.ys.iter().map(|r| sk.g_tilda.mul_by_scalar(r)).collect();
-        let x_tilda = sk.g_tilda.mul_by_scalar(&sk.x);
-        PublicKey {
-            g: sk.g,
-            g_tilda: sk.g_tilda,
-            ys,
-            y_tildas,
-            x_tilda,
-        }
+impl<C: Pairing> Deserial for PublicKey<C> {
+    /// Same format as the derived instance, but rejects keys where `ys` and
+    /// `y_tildas` differ in length.
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        Ok(Self::deserial_worker(source, None)?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::secret::SecretKey;
     use pairing::bls12_381::Bls12;
 
     macro_rules! macro_test_public_key_to_byte_conversion {
@@ -127,6 +219,74 @@ mod tests {
 
     macro_test_public_key_to_byte_conversion!(public_key_to_byte_conversion_bls12_381, Bls12);
 
+    #[test]
+    pub fn public_key_from_bytes() {
+        let mut csprng = thread_rng();
+        for n in 0..5 {
+            let pk = PublicKey::<Bls12>::arbitrary(n, &mut csprng);
+            let bytes = to_bytes(&pk);
+            let pk2 = PublicKey::<Bls12>::from_bytes(&bytes).expect("Valid public key.");
+            assert_eq!(pk, pk2);
+            assert_eq!(pk.message_count(), n);
+            assert_eq!(
+                pk.byte_len(),
+                bytes.len(),
+                "Byte length for {} messages.",
+                n
+            );
+            assert!(
+                PublicKey::<Bls12>::deserial_with_count(&mut std::io::Cursor::new(&bytes), n + 1)
+                    .is_err(),
+                "Wrong count should be rejected."
+            );
+            assert!(
+                PublicKey::<Bls12>::from_bytes(&bytes[1..]).is_err(),
+                "Wrong length should be rejected."
+            );
+        }
+    }
+
+    #[test]
+    pub fn public_key_mismatched_lengths() {
+        let mut csprng = thread_rng();
+        let mut pk = PublicKey::<Bls12>::arbitrary(3, &mut csprng);
+        pk.y_tildas.pop();
+        assert!(
+            serialize_deserialize(&pk).is_err(),
+            "Keys with fewer y_tildas than ys should be rejected."
+        );
+    }
+
+    #[test]
+    pub fn public_key_truncated_index() {
+        let mut csprng = thread_rng();
+        let n = 3;
+        let pk = PublicKey::<Bls12>::arbitrary(n, &mut csprng);
+        let bytes = to_bytes(&pk);
+        // Truncate within the second G2 element of y_tildas, which is element
+        // 2 + n + 1 counting from g.
+        let offset = 48 + 96 + 4 + 48 * n + 4 + 96 + 10;
+        match PublicKey::<Bls12>::deserial_with_count(
+            &mut std::io::Cursor::new(&bytes[..offset]),
+            n,
+        ) {
+            Err(SignatureError(InternalError::PublicKeyElementError { index })) => {
+                assert_eq!(index, 2 + n + 1)
+            }
+            other => panic!("Unexpected result {:?}.", other),
+        }
+        // Truncate within x_tilda.
+        match PublicKey::<Bls12>::deserial_with_count(
+            &mut std::io::Cursor::new(&bytes[..bytes.len() - 1]),
+            n,
+        ) {
+            Err(SignatureError(InternalError::PublicKeyElementError { index })) => {
+                assert_eq!(index, 2 + 2 * n)
+            }
+            other => panic!("Unexpected result {:?}.", other),
+        }
+    }
+
     macro_rules! macro_test_sign_verify_pass {
         ($function_name:ident, $pairing_type:path) => {
             #[test]
@@ -209,4 +369,73 @@ mod tests {
     }
 
     macro_test_sign_verify_dummy_sig!(sign_verify_dummy_sig_bls12_381, Bls12);
+
+    #[test]
+    pub fn verify_rejects_identity_signature() {
+        type G1 = <Bls12 as Pairing>::G1;
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<Bls12>::generate(3, &mut csprng);
+        let pk = PublicKey::from(&sk);
+        let message = KnownMessage::<Bls12>::generate(3, &mut csprng);
+        let zero = Signature::<Bls12>(G1::zero_point(), G1::zero_point());
+        // The pairing equation alone holds trivially for the identity
+        // signature, so only the explicit check rejects it.
+        assert!(Bls12::check_pairing_eq(
+            &zero.0,
+            &pk.x_tilda,
+            &zero.1,
+            &pk.g_tilda
+        ));
+        assert!(!pk.verify(&zero, &message));
+        // sigma_1 = 0 is rejected regardless of sigma_2.
+        let sig = sk
+            .sign_known_message(&message, &mut csprng)
+            .expect("Signing should succeed.");
+        assert!(pk.verify(&sig, &message));
+        assert!(!pk.verify(&Signature(G1::zero_point(), sig.1), &message));
+    }
+
+    /// Verification needs nothing but the public key and the signature, so
+    /// this module deliberately does not import the secret key.
+    mod verify_only {
+        use crate::{KnownMessage, PublicKey, Signature};
+        use crypto_common::*;
+        use curve_arithmetic::*;
+        use pairing::bls12_381::{Bls12, G1};
+
+        /// Key for signing 2 messages, and its signature on the message
+        /// `[17, 4242]`, generated with a different instance of this crate.
+        const PUBLIC_KEY: &str =
+            "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aef\
+            fb3af00adb22c6bb93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049\
+            334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02\
+            b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb80000000293d5e2f874297dc35a7e5490\
+            3145a53e60c58214c6a2b639a64eb217bf3a9d4ae8ff954f83358dd7dd5c33fcd47c692c8152c432\
+            bc19cc392455675b5bcc94109562f069ea117e3355898d4dc44b37713639d0a6481d0388f302aae3\
+            4bacaca6000000029430d417220f99e32539ece2cc738905932d9528b80f82b7213a4fe529bc15b4\
+            b090b8fa0b3dbbca3990f6a51c3d2bd6189f6954f77817fb6bd97d35f24c747f876d59bf2bde46f8\
+            1b707eaf5c031b72caf0dbafee93d16666b9f1a967ce9a72a47bf2b7f3d905ff2fb8cba6af55f1e6\
+            3e3b70e488c13efd51b68734fe4d3bfb846aaa81998b6d911f563d73995fcae805655cd0995d7c2d\
+            c6decb18b60de42e85ab50248db5ea9910535873a1d69c1348c98acbee49c956038292690b0420b8\
+            8ae577893730ea4fefa587ba5ded4afab229576d3c10cd3a74b437199524ffbf6c6cc43d5e8bd467\
+            9a4dd796f620523b0aac2a327c884b858964a15dd041356f590e045a21b8fad5dedef89cd83998cf\
+            d8de90045353f0a17561278afb42e670";
+        const SIGNATURE: &str =
+            "93e34a604ff95b4dfcd9c4a47c6f4015762a746a73ec18d252a43b62944b14665833a0695ab4fed9\
+            07642dc774fef69895802d20b4b66392cdc28cd7453caf5a618b52829b096c19a54873a79a9d4626\
+            d26cfd2b461c781b322a52ad0bbfc5cb";
+
+        #[test]
+        pub fn verify_test_vector() {
+            let pk: PublicKey<Bls12> = base16_decode_string(PUBLIC_KEY).expect("Valid public key.");
+            assert_eq!(pk.message_count(), 2);
+            let sig: Signature<Bls12> = base16_decode_string(SIGNATURE).expect("Valid signature.");
+            let message =
+                KnownMessage::<Bls12>(vec![G1::scalar_from_u64(17), G1::scalar_from_u64(4242)]);
+            assert!(pk.verify(&sig, &message), "Signature should verify.");
+            let other =
+                KnownMessage::<Bls12>(vec![G1::scalar_from_u64(17), G1::scalar_from_u64(4243)]);
+            assert!(!pk.verify(&sig, &other), "Other message should not verify.");
+        }
+    }
 }
@@ -5,6 +5,7 @@
 use crate::{
     errors::{InternalError::SecretKeyLengthError, *},
     known_message::*,
+    public::PublicKey,
     signature::*,
     unknown_message::*,
 };
@@ -14,6 +15,7 @@ use curve_arithmetic::*;
 use ff::Field;
 
 use rand::*;
+use zeroize::Zeroizing;
 
 /// A secret key
 #[derive(Debug, Serialize)]
@@ -54,6 +56,31 @@ impl<C: Pairing> SecretKey<C> {
         }
     }
 
+    /// Serialize the secret key. The buffer is allocated with the exact size
+    /// of the serialization up front, so no copies of the secret scalars are
+    /// left behind by reallocation, and it is zeroed when dropped.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let len = C::G1::GROUP_ELEMENT_LENGTH
+            + C::G2::GROUP_ELEMENT_LENGTH
+            + 4
+            + (self.ys.len() + 1) * C::G1::SCALAR_LENGTH;
+        let mut out = Zeroizing::new(Vec::with_capacity(len));
+        out.put(self);
+        out
+    }
+
+    /// Deserialize a secret key from bytes produced by
+    /// [SecretKey::to_bytes]. All the bytes must be consumed, and scalars
+    /// which are not canonically encoded are rejected.
+    pub fn from_bytes(bytes: &[u8]) -> ParseResult<Self> {
+        let mut source = std::io::Cursor::new(bytes);
+        let sk = source.get()?;
+        if source.position() != bytes.len() as u64 {
+            anyhow::bail!("Trailing bytes after the secret key.")
+        }
+        Ok(sk)
+    }
+
     pub fn sign_known_message<T>(
         &self,
         message: &KnownMessage<C>,
@@ -99,6 +126,22 @@ impl<C: Pairing> SecretKey<C> {
     }
 }
 
+impl<C: Pairing> From<&SecretKey<C>> for PublicKey<C> {
+    /// Derive this public key from its corresponding `SecretKey`.
+    fn from(sk: &SecretKey<C>) -> PublicKey<C> {
+        let ys = sk.ys.iter().map(|r| sk.g.mul_by_scalar(r)).collect();
+        let y_tildas = sk.ys.iter().map(|r| sk.g_tilda.mul_by_scalar(r)).collect();
+        let x_tilda = sk.g_tilda.mul_by_scalar(&sk.x);
+        PublicKey {
+            g: sk.g,
+            g_tilda: sk.g_tilda,
+            ys,
+            y_tildas,
+            x_tilda,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,4 +164,46 @@ mod tests {
     }
 
     macro_test_secret_key_to_byte_conversion!(secret_key_to_byte_conversion_bls12_381, Bls12);
+
+    #[test]
+    pub fn secret_key_to_from_bytes() {
+        let mut csprng = thread_rng();
+        for i in 0..20 {
+            let sk = SecretKey::<Bls12>::generate(i, &mut csprng);
+            let bytes = sk.to_bytes();
+            let sk2 = SecretKey::<Bls12>::from_bytes(&bytes).expect("Round trip should succeed.");
+            assert_eq!(sk, sk2);
+            let mut longer = bytes.to_vec();
+            longer.push(0);
+            assert!(
+                SecretKey::<Bls12>::from_bytes(&longer).is_err(),
+                "Trailing bytes."
+            );
+            // The last scalar is x, which is not canonical if all bits are set.
+            let mut non_canonical = bytes.to_vec();
+            let n = non_canonical.len();
+            non_canonical[n - 32..].copy_from_slice(&[0xff; 32]);
+            assert!(
+                SecretKey::<Bls12>::from_bytes(&non_canonical).is_err(),
+                "Non-canonical x."
+            );
+        }
+    }
+
+    /// Zeroizing on drop only clears the final allocation, so the buffer must
+    /// be allocated with its exact size and never grow.
+    #[test]
+    pub fn secret_key_to_bytes_exact_capacity() {
+        let mut csprng = thread_rng();
+        for i in 0..20 {
+            let sk = SecretKey::<Bls12>::generate(i, &mut csprng);
+            let bytes = sk.to_bytes();
+            assert_eq!(
+                bytes.len(),
+                bytes.capacity(),
+                "The buffer should not be reallocated for {} messages.",
+                i
+            );
+        }
+    }
 }
@@ -7,6 +7,9 @@ use curve_arithmetic::Curve;
 use sha3::{Digest, Sha3_256};
 use std::io::Write;
 
+mod transcript;
+pub use transcript::*;
+
 /// State of the random oracle, used to incrementally build up the output.
 #[repr(transparent)]
 #[derive(Debug)]
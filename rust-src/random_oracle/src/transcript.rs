@@ -0,0 +1,98 @@
+//! A transcript for Fiat-Shamir challenges with strict domain separation.
+use crypto_common::*;
+use curve_arithmetic::Curve;
+use sha3::{Digest, Sha3_256};
+
+/// A transcript of a protocol, from which challenges are derived. Every
+/// message is appended together with a label, and both the label and the
+/// message are prefixed by their length. This makes the encoding of the
+/// transcript unambiguous, so that two different sequences of messages never
+/// lead to the same challenge.
+#[derive(Debug, Clone)]
+pub struct Transcript(Sha3_256);
+
+impl Transcript {
+    /// Start a new transcript for the given protocol.
+    pub fn new<B: AsRef<[u8]>>(domain: B) -> Self {
+        let mut transcript = Transcript(Sha3_256::new());
+        transcript.append_message("domain", domain);
+        transcript
+    }
+
+    // Append the length of the data in big endian, followed by the data.
+    fn append_with_length(&mut self, data: &[u8]) {
+        self.0.update((data.len() as u64).to_be_bytes());
+        self.0.update(data);
+    }
+
+    /// Append a message to the transcript, using `label` as domain
+    /// separation.
+    pub fn append_message<L: AsRef<[u8]>, B: AsRef<[u8]>>(&mut self, label: L, message: B) {
+        self.append_with_length(label.as_ref());
+        self.append_with_length(message.as_ref());
+    }
+
+    /// Append the serialization of the value to the transcript, using `label`
+    /// as domain separation.
+    pub fn append_serial<L: AsRef<[u8]>, S: Serial>(&mut self, label: L, message: &S) {
+        self.append_message(label, to_bytes(message))
+    }
+
+    /// Derive a challenge from the current state of the transcript, using
+    /// `label` as domain separation. The label is appended to the transcript,
+    /// so that subsequent challenges are different.
+    pub fn challenge_scalar<C: Curve, L: AsRef<[u8]>>(&mut self, label: L) -> C::Scalar {
+        self.append_message(label, []);
+        C::hash_to_scalar(&self.0.clone().finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::G1;
+
+    #[test]
+    pub fn test_transcript_deterministic() {
+        let mut t1 = Transcript::new("test");
+        t1.append_message("a", b"hello");
+        t1.append_serial("b", &17u64);
+        let mut t2 = t1.clone();
+        assert_eq!(
+            t1.challenge_scalar::<G1, _>("c"),
+            t2.challenge_scalar::<G1, _>("c"),
+            "Identical transcripts should give identical challenges."
+        );
+        assert_ne!(
+            t1.challenge_scalar::<G1, _>("c"),
+            t2.challenge_scalar::<G1, _>("d"),
+            "Subsequent challenges should differ."
+        );
+    }
+
+    #[test]
+    pub fn test_transcript_order_and_ambiguity() {
+        let mut t1 = Transcript::new("test");
+        t1.append_message("a", b"x");
+        t1.append_message("b", b"y");
+        let mut t2 = Transcript::new("test");
+        t2.append_message("b", b"y");
+        t2.append_message("a", b"x");
+        assert_ne!(
+            t1.challenge_scalar::<G1, _>("c"),
+            t2.challenge_scalar::<G1, _>("c"),
+            "Reordering appends should change the challenge."
+        );
+
+        // Moving bytes between the label and the message must be detected.
+        let mut t1 = Transcript::new("test");
+        t1.append_message("ab", b"c");
+        let mut t2 = Transcript::new("test");
+        t2.append_message("a", b"bc");
+        assert_ne!(
+            t1.challenge_scalar::<G1, _>("c"),
+            t2.challenge_scalar::<G1, _>("c"),
+            "Label and message boundaries should be unambiguous."
+        );
+    }
+}